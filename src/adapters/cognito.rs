@@ -0,0 +1,188 @@
+//! `cognito` module maps users of an [`Amazon Cognito`] user pool into subject identifiers. The
+//! "sub" attribute, scoped to the user pool issuer URL, yields an "iss_sub" identifier; verified
+//! "email" and "phone_number" attributes are added as aliases.
+//!
+//! [`Amazon Cognito`]: https://docs.aws.amazon.com/cognito/latest/developerguide/user-pool-settings-attributes.html
+
+use crate::{Aliases, Atomic, EmailAddress, Error, SubjectId};
+
+/// Issuer URL of a Cognito user pool, i.e. the "iss" claim of the tokens it issues. The region is
+/// taken from the user pool ID, which has the form `<region>_<id>`: the region must be an AWS
+/// region name such as "us-east-1" or "us-gov-west-1", and the id must be alphanumeric.
+///
+/// ```
+/// use subject_id::adapters::cognito;
+/// let issuer = cognito::issuer_url("us-east-1_AbCdEfGhI").unwrap();
+/// assert_eq!(issuer, "https://cognito-idp.us-east-1.amazonaws.com/us-east-1_AbCdEfGhI");
+/// ```
+pub fn issuer_url(user_pool_id: &str) -> Result<String, Error> {
+    match user_pool_id.split_once('_') {
+        Some((region, id))
+            if is_region(region)
+                && !id.is_empty()
+                && id.bytes().all(|b| b.is_ascii_alphanumeric()) =>
+        {
+            Ok(format!(
+                "https://cognito-idp.{region}.amazonaws.com/{user_pool_id}"
+            ))
+        }
+        _ => Err(Error::InvalidUserPool),
+    }
+}
+
+/// Report whether the name has the shape of an AWS region, `[a-z]{2}(-[a-z]+)+-[0-9]`.
+fn is_region(name: &str) -> bool {
+    let parts: Vec<_> = name.split('-').collect();
+    let is_lower = |part: &str| !part.is_empty() && part.bytes().all(|b| b.is_ascii_lowercase());
+    let last = parts[parts.len() - 1];
+    parts.len() >= 3
+        && parts[0].len() == 2
+        && parts[..parts.len() - 1].iter().all(|part| is_lower(part))
+        && last.len() == 1
+        && last.bytes().all(|b| b.is_ascii_digit())
+}
+
+/// Map the attributes of a Cognito user into a [SubjectId]. The "sub" attribute is REQUIRED and
+/// must not be empty, and a verified "email" attribute must be an email address.
+/// The "email" and "phone_number" attributes are only used when the matching "email_verified"
+/// and "phone_number_verified" attributes are "true", since unverified values cannot be relied
+/// upon to identify the user. The result is an "iss_sub" identifier when no other attribute is
+/// used, and "aliases" otherwise.
+///
+/// ```
+/// use subject_id::adapters::cognito;
+/// use subject_id::SubjectId;
+/// let attributes = [
+///     ("sub", "3f8b2c1e-5d6a-4b7c-8e9f-0a1b2c3d4e5f"),
+///     ("email", "user@example.com"),
+///     ("email_verified", "true"),
+/// ];
+/// let sub_id = cognito::subject_id("us-east-1_AbCdEfGhI", attributes).unwrap();
+/// assert_eq!(sub_id.format(), SubjectId::FORMAT_ALIASES);
+/// ```
+pub fn subject_id<'a>(
+    user_pool_id: &str,
+    attributes: impl IntoIterator<Item = (&'a str, &'a str)>,
+) -> Result<SubjectId, Error> {
+    let mut sub = None;
+    let mut email = None;
+    let mut phone_number = None;
+    let mut email_verified = false;
+    let mut phone_number_verified = false;
+    for (name, value) in attributes {
+        match name {
+            "sub" => sub = Some(value),
+            "email" => email = Some(value),
+            "email_verified" => email_verified = value == "true",
+            "phone_number" => phone_number = Some(value),
+            "phone_number_verified" => phone_number_verified = value == "true",
+            _ => {}
+        }
+    }
+    let subject = sub
+        .filter(|sub| !sub.is_empty())
+        .ok_or(Error::MissingAttribute("sub"))?;
+    let mut identifiers = vec![Atomic::IssuerSubject {
+        issuer: issuer_url(user_pool_id)?,
+        subject: subject.to_owned(),
+    }];
+    if let Some(email) = email.filter(|_| email_verified) {
+        email.parse::<EmailAddress>()?;
+        identifiers.push(Atomic::Email {
+            email: email.to_owned(),
+        });
+    }
+    if let Some(phone_number) = phone_number.filter(|_| phone_number_verified) {
+        identifiers.push(Atomic::PhoneNumber {
+            phone_number: phone_number.parse()?,
+        });
+    }
+    if identifiers.len() == 1 {
+        return Ok(SubjectId::Atomic(identifiers.remove(0)));
+    }
+    Ok(SubjectId::Aliases(Aliases { identifiers }))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const POOL: &str = "eu-west-1_AbCdEfGhI";
+    const ISSUER: &str = "https://cognito-idp.eu-west-1.amazonaws.com/eu-west-1_AbCdEfGhI";
+
+    #[test]
+    fn test_subject_id() {
+        let attributes = [
+            ("sub", "3f8b2c1e"),
+            ("email", "user@example.com"),
+            ("email_verified", "false"),
+            ("phone_number", "+12065550100"),
+            ("phone_number_verified", "true"),
+        ];
+        let got = subject_id(POOL, attributes).unwrap();
+        let want = SubjectId::Aliases(Aliases::from(vec![
+            Atomic::IssuerSubject {
+                issuer: ISSUER.to_owned(),
+                subject: "3f8b2c1e".to_owned(),
+            },
+            Atomic::PhoneNumber {
+                phone_number: "+12065550100".parse().unwrap(),
+            },
+        ]));
+        assert_eq!(got, want);
+    }
+
+    #[test]
+    fn test_subject_id_sub_only() {
+        let got = subject_id(POOL, [("sub", "3f8b2c1e")]).unwrap();
+        let want = SubjectId::Atomic(Atomic::IssuerSubject {
+            issuer: ISSUER.to_owned(),
+            subject: "3f8b2c1e".to_owned(),
+        });
+        assert_eq!(got, want);
+    }
+
+    #[test]
+    fn test_subject_id_invalid() {
+        let got = subject_id(POOL, [("email", "user@example.com")]);
+        assert!(matches!(got, Err(Error::MissingAttribute("sub"))));
+        let got = subject_id(POOL, [("sub", "")]);
+        assert!(matches!(got, Err(Error::MissingAttribute("sub"))));
+        let attributes = [
+            ("sub", "3f8b2c1e"),
+            ("email", ""),
+            ("email_verified", "true"),
+        ];
+        assert!(matches!(
+            subject_id(POOL, attributes),
+            Err(Error::InvalidEmail)
+        ));
+        let got = subject_id("AbCdEfGhI", [("sub", "3f8b2c1e")]);
+        assert!(matches!(got, Err(Error::InvalidUserPool)));
+    }
+
+    #[test]
+    fn test_issuer_url() {
+        for pool in ["us-east-1_AbCdEfGhI", "us-gov-west-1_AbCdEfGhI"] {
+            assert!(issuer_url(pool).is_ok(), "{pool}");
+        }
+        let invalid = [
+            "_AbCdEfGhI",
+            "us-east-1_",
+            "useast1_AbCdEfGhI",
+            "us-1_AbCdEfGhI",
+            "US-east-1_AbCdEfGhI",
+            "us-east-12_AbCdEfGhI",
+            "evil.example/x-east-1_AbCdEfGhI",
+            "us-east-1.evil.example_AbCdEfGhI",
+            "us-east-1_AbC/../x",
+            "us-east-1_AbC_dEf",
+        ];
+        for pool in invalid {
+            assert!(
+                matches!(issuer_url(pool), Err(Error::InvalidUserPool)),
+                "{pool}"
+            );
+        }
+    }
+}
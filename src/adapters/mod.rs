@@ -0,0 +1,4 @@
+//! `adapters` module maps the user models of identity providers into subject identifiers, for
+//! applications emitting Security Event Tokens about the users of these providers.
+
+pub mod cognito;
@@ -0,0 +1,92 @@
+//! `annotated` module defines [AnnotatedSubjectId], which carries metadata about where a subject
+//! identifier came from alongside the identifier itself. The metadata never goes on the wire:
+//! serialization emits only the spec-conformant subject identifier.
+
+use crate::SubjectId;
+use serde::{Deserialize, Serialize};
+use std::time::SystemTime;
+
+/// [AnnotatedSubjectId] wraps a [SubjectId] with non-wire provenance metadata. It serializes and
+/// deserializes exactly as the wrapped [SubjectId]; deserialized values carry empty metadata.
+///
+/// ```
+/// use std::time::SystemTime;
+/// use subject_id::{AnnotatedSubjectId, Atomic, SubjectId, Verification};
+/// let sub_id = SubjectId::Atomic(Atomic::Opaque { id: "1i3j4l".to_owned() });
+/// let annotated = AnnotatedSubjectId {
+///     source: Some("idp-eu".to_owned()),
+///     received_at: Some(SystemTime::now()),
+///     verification: Verification::Verified,
+///     ..AnnotatedSubjectId::from(sub_id)
+/// };
+/// let json = serde_json::to_string(&annotated).unwrap();
+/// assert_eq!(json, r#"{"format":"opaque","id":"1i3j4l"}"#);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct AnnotatedSubjectId {
+    /// The subject identifier, which is the only part put on the wire.
+    pub subject: SubjectId,
+    /// Name of the system the subject identifier was received from.
+    #[serde(skip)]
+    pub source: Option<String>,
+    /// Time at which the subject identifier was received.
+    #[serde(skip)]
+    pub received_at: Option<SystemTime>,
+    /// Outcome of verifying the Security Event Token that carried the subject identifier.
+    #[serde(skip)]
+    pub verification: Verification,
+}
+
+/// [Verification] reports whether the Security Event Token that carried a subject identifier was
+/// verified by the receiver.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Verification {
+    /// The token has not been verified (yet).
+    #[default]
+    Unverified,
+    /// The token signature and claims were verified.
+    Verified,
+    /// Verification of the token failed.
+    Failed,
+}
+
+impl AnnotatedSubjectId {
+    /// Discard the metadata and return the wrapped [SubjectId].
+    pub fn into_inner(self) -> SubjectId {
+        self.subject
+    }
+}
+
+impl From<SubjectId> for AnnotatedSubjectId {
+    fn from(subject: SubjectId) -> Self {
+        Self {
+            subject,
+            source: None,
+            received_at: None,
+            verification: Verification::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Atomic;
+
+    #[test]
+    fn test_annotated_wire_format() {
+        let sub_id = SubjectId::Atomic(Atomic::Email {
+            email: "user@example.com".to_owned(),
+        });
+        let annotated = AnnotatedSubjectId {
+            source: Some("idp-eu".to_owned()),
+            verification: Verification::Failed,
+            ..AnnotatedSubjectId::from(sub_id.clone())
+        };
+        let json = serde_json::to_value(&annotated).unwrap();
+        assert_eq!(json, serde_json::to_value(&sub_id).unwrap());
+        let got: AnnotatedSubjectId = serde_json::from_value(json).unwrap();
+        assert_eq!(got, AnnotatedSubjectId::from(sub_id));
+    }
+}
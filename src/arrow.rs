@@ -0,0 +1,134 @@
+//! `arrow` module converts subject identifiers to and from [`Apache Arrow`] record batches, so that
+//! analytics jobs over event archives can work on columns instead of parsing JSON strings row by
+//! row. Record batches can be written as Parquet with the `parquet` crate. This module is
+//! available behind the `arrow` feature.
+//!
+//! Every row holds one subject identifier, with the following columns:
+//!
+//! - `format`: the subject identifier format, never null.
+//! - `value`: the value that identifies the subject (see [`Atomic::value`]), null for "aliases".
+//! - `issuer`: the "iss" member of an "iss_sub" identifier, null for other formats.
+//! - `json`: the subject identifier serialized as JSON, never null.
+//!
+//! The `json` column is authoritative; the other columns are derived from it for querying.
+//!
+//! [`Apache Arrow`]: https://arrow.apache.org/
+
+use crate::{Atomic, Error, SubjectId};
+use arrow_array::{Array, ArrayRef, RecordBatch, StringArray};
+use arrow_schema::{ArrowError, DataType, Field, Schema};
+use std::sync::Arc;
+
+const COLUMN_FORMAT: &str = "format";
+const COLUMN_VALUE: &str = "value";
+const COLUMN_ISSUER: &str = "issuer";
+const COLUMN_JSON: &str = "json";
+
+/// Arrow [Schema] of the record batches produced by [`to_record_batch`].
+pub fn schema() -> Schema {
+    Schema::new(vec![
+        Field::new(COLUMN_FORMAT, DataType::Utf8, false),
+        Field::new(COLUMN_VALUE, DataType::Utf8, true),
+        Field::new(COLUMN_ISSUER, DataType::Utf8, true),
+        Field::new(COLUMN_JSON, DataType::Utf8, false),
+    ])
+}
+
+/// Convert subject identifiers into a [RecordBatch] with one row per subject identifier.
+///
+/// ```
+/// use subject_id::{Atomic, SubjectId};
+/// let sub_ids = vec![SubjectId::Atomic(Atomic::Opaque { id: "1i3j4l".to_owned() })];
+/// let batch = subject_id::arrow::to_record_batch(&sub_ids).unwrap();
+/// assert_eq!(batch.num_rows(), 1);
+/// ```
+pub fn to_record_batch(sub_ids: &[SubjectId]) -> Result<RecordBatch, Error> {
+    let format: StringArray = sub_ids.iter().map(|id| Some(id.format())).collect();
+    let value: StringArray = sub_ids
+        .iter()
+        .map(|id| match id {
+            SubjectId::Atomic(id) => Some(id.value()),
+            SubjectId::Aliases(..) => None,
+        })
+        .collect();
+    let issuer: StringArray = sub_ids
+        .iter()
+        .map(|id| match id {
+            SubjectId::Atomic(Atomic::IssuerSubject { issuer, .. }) => Some(issuer.as_str()),
+            _ => None,
+        })
+        .collect();
+    let json = sub_ids
+        .iter()
+        .map(|id| serde_json::to_string(id).map(Some))
+        .collect::<Result<StringArray, _>>()?;
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(format),
+        Arc::new(value),
+        Arc::new(issuer),
+        Arc::new(json),
+    ];
+    Ok(RecordBatch::try_new(Arc::new(schema()), columns)?)
+}
+
+/// Convert a [RecordBatch] back into subject identifiers. Only the `json` column is read, so the
+/// batch may carry additional columns or omit the derived ones.
+pub fn from_record_batch(batch: &RecordBatch) -> Result<Vec<SubjectId>, Error> {
+    let json = batch
+        .column_by_name(COLUMN_JSON)
+        .and_then(|column| column.as_any().downcast_ref::<StringArray>())
+        .ok_or_else(|| schema_error("missing utf8 column \"json\""))?;
+    if json.null_count() > 0 {
+        return Err(schema_error("column \"json\" contains null values"));
+    }
+    json.iter()
+        .flatten()
+        .map(|value| Ok(serde_json::from_str(value)?))
+        .collect()
+}
+
+fn schema_error(message: &str) -> Error {
+    Error::Arrow(ArrowError::SchemaError(message.to_owned()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Aliases;
+
+    #[test]
+    fn test_record_batch_round_trip() {
+        let sub_ids = vec![
+            SubjectId::Atomic(Atomic::IssuerSubject {
+                issuer: "https://issuer.example.com/".to_owned(),
+                subject: "145234573".to_owned(),
+            }),
+            SubjectId::Aliases(Aliases {
+                identifiers: vec![Atomic::Email {
+                    email: "user@example.com".to_owned(),
+                }],
+            }),
+        ];
+        let batch = to_record_batch(&sub_ids).unwrap();
+        let column = |name| {
+            let column = batch.column_by_name(name).unwrap();
+            let column = column.as_any().downcast_ref::<StringArray>().unwrap();
+            column.iter().collect::<Vec<_>>()
+        };
+        assert_eq!(column(COLUMN_FORMAT), [Some("iss_sub"), Some("aliases")]);
+        assert_eq!(column(COLUMN_VALUE), [Some("145234573"), None]);
+        assert_eq!(
+            column(COLUMN_ISSUER),
+            [Some("https://issuer.example.com/"), None]
+        );
+        assert_eq!(from_record_batch(&batch).unwrap(), sub_ids);
+    }
+
+    #[test]
+    fn test_from_record_batch_missing_json() {
+        let schema = Schema::new(vec![Field::new(COLUMN_FORMAT, DataType::Utf8, false)]);
+        let format = StringArray::from(vec!["opaque"]);
+        let batch = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(format)]).unwrap();
+        assert!(matches!(from_record_batch(&batch), Err(Error::Arrow(..))));
+    }
+}
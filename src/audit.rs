@@ -0,0 +1,184 @@
+//! `audit` module renders subject identifiers as deterministic single-line strings, meant for
+//! grep-able audit logs where multi-line JSON is unacceptable. The rendering lists the "format"
+//! member followed by the other members of the Identifier Format, in the order the format defines
+//! them. Member values are always quoted and escaped, so the output never spans multiple lines.
+//!
+//! ```text
+//! format=email email="user@example.com"
+//! format=aliases identifiers=[{format=email email="u***@example.com"},{format=opaque id="1***"}]
+//! ```
+
+use crate::{Atomic, SubjectId};
+use std::fmt::Write;
+
+impl SubjectId {
+    /// Render the [SubjectId] as a stable, escaped, single-line string for audit logs.
+    ///
+    /// ```
+    /// use subject_id::{Atomic, SubjectId};
+    /// let sub_id = SubjectId::Atomic(Atomic::Email { email: "user@example.com".to_owned() });
+    /// assert_eq!(sub_id.to_audit_string(), r#"format=email email="user@example.com""#);
+    /// ```
+    pub fn to_audit_string(&self) -> String {
+        self.audit(false)
+    }
+
+    /// Same as [`to_audit_string`], except that member values which may identify a person are
+    /// masked. See [`Atomic::masked_members`] for the masking rules.
+    ///
+    /// [`to_audit_string`]: SubjectId::to_audit_string
+    ///
+    /// ```
+    /// use subject_id::{Atomic, SubjectId};
+    /// let sub_id = SubjectId::Atomic(Atomic::Email { email: "user@example.com".to_owned() });
+    /// assert_eq!(sub_id.to_masked_audit_string(), r#"format=email email="u***@example.com""#);
+    /// ```
+    pub fn to_masked_audit_string(&self) -> String {
+        self.audit(true)
+    }
+
+    fn audit(&self, masked: bool) -> String {
+        let mut out = String::new();
+        match self {
+            Self::Atomic(id) => write_atomic(&mut out, id, masked),
+            Self::Aliases(aliases) => {
+                out.push_str("format=aliases identifiers=[");
+                for (i, id) in aliases.identifiers.iter().enumerate() {
+                    if i > 0 {
+                        out.push(',');
+                    }
+                    out.push('{');
+                    write_atomic(&mut out, id, masked);
+                    out.push('}');
+                }
+                out.push(']');
+            }
+        }
+        out
+    }
+}
+
+impl Atomic {
+    /// Members of the atomic identifier (other than "format") paired with their member names,
+    /// where the values that may identify a person are masked:
+    ///
+    /// - the local part of an email address or an "acct" URI keeps only its first character,
+    /// - a phone number keeps only its last two digits,
+    /// - the "iss" member is kept as is, since it identifies the issuer and not the subject,
+    /// - any other value keeps only its first character.
+    ///
+    /// ```
+    /// use subject_id::Atomic;
+    /// let sub_id = Atomic::PhoneNumber { phone_number: "+12065550100".parse().unwrap() };
+    /// assert_eq!(sub_id.masked_members(), vec![("phone_number", "+***00".to_owned())]);
+    /// ```
+    pub fn masked_members(&self) -> Vec<(&'static str, String)> {
+        self.members()
+            .into_iter()
+            .map(|(name, value)| {
+                let masked = match self {
+                    Atomic::Email { .. } => mask_address(value),
+                    Atomic::Account { .. } => match value.strip_prefix("acct:") {
+                        Some(rest) => "acct:".to_owned() + &mask_address(rest),
+                        None => mask_prefix(value),
+                    },
+                    Atomic::PhoneNumber { .. } => {
+                        let digits = value.trim_start_matches('+');
+                        let keep = digits.len().saturating_sub(2);
+                        "+***".to_owned() + &digits[keep..]
+                    }
+                    Atomic::IssuerSubject { .. } if name == "issuer" => value.to_owned(),
+                    _ => mask_prefix(value),
+                };
+                (name, masked)
+            })
+            .collect()
+    }
+}
+
+fn write_atomic(out: &mut String, id: &Atomic, masked: bool) {
+    let members = if masked {
+        id.masked_members()
+    } else {
+        id.members()
+            .into_iter()
+            .map(|(name, value)| (name, value.to_owned()))
+            .collect()
+    };
+    out.push_str("format=");
+    out.push_str(id.format());
+    for (name, value) in members {
+        let _ = write!(out, " {name}=\"");
+        escape_into(out, &value);
+        out.push('"');
+    }
+}
+
+/// Escape backslash, double quote and every control character, so that the value can be safely
+/// placed between double quotes on a single line.
+fn escape_into(out: &mut String, value: &str) {
+    for c in value.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            '"' => out.push_str("\\\""),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c.is_control() => {
+                let _ = write!(out, "\\u{{{:04x}}}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+}
+
+fn mask_address(value: &str) -> String {
+    match value.rsplit_once('@') {
+        Some((local, domain)) => mask_prefix(local) + "@" + domain,
+        None => mask_prefix(value),
+    }
+}
+
+fn mask_prefix(value: &str) -> String {
+    let mut masked: String = value.chars().take(1).collect();
+    masked.push_str("***");
+    masked
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Aliases;
+
+    #[test]
+    fn test_audit_string() {
+        let sub_id = SubjectId::Aliases(Aliases {
+            identifiers: vec![
+                Atomic::IssuerSubject {
+                    issuer: "https://issuer.example.com/".to_owned(),
+                    subject: "1452\"34573\n".to_owned(),
+                },
+                Atomic::PhoneNumber {
+                    phone_number: "+12065550100".parse().unwrap(),
+                },
+            ],
+        });
+        assert_eq!(
+            sub_id.to_audit_string(),
+            r#"format=aliases identifiers=[{format=iss_sub issuer="https://issuer.example.com/" subject="1452\"34573\n"},{format=phone_number phone_number="+12065550100"}]"#,
+        );
+        assert_eq!(
+            sub_id.to_masked_audit_string(),
+            r#"format=aliases identifiers=[{format=iss_sub issuer="https://issuer.example.com/" subject="1***"},{format=phone_number phone_number="+***00"}]"#,
+        );
+    }
+
+    #[test]
+    fn test_masked_account() {
+        let sub_id = Atomic::Account {
+            uri: "acct:example.user@service.example.com".to_owned(),
+        };
+        let want = vec![("uri", "acct:e***@service.example.com".to_owned())];
+        assert_eq!(sub_id.masked_members(), want);
+    }
+}
@@ -0,0 +1,402 @@
+//! `canonical` module defines how subject identifiers are canonicalized before they are compared
+//! or correlated. Canonicalization is controlled by a [CanonicalPolicy], so that receivers can
+//! choose how aggressively identifiers are folded.
+//!
+//! Canonicalization is the [NormalizationPipeline] derived from the policy; use a pipeline
+//! directly to apply further normalization steps.
+//!
+//! Only the formats whose members are (partly) case-insensitive are affected: the domain of an
+//! email address and the "acct" URI scheme and host are case-insensitive, while local parts are
+//! case-sensitive by specification even though most mail systems ignore their case. Members of
+//! the other formats are case-sensitive and are never changed.
+//!
+//! Subject identifiers are totally ordered over their canonical form under the default policy:
+//!
+//! 1. by format name, so "aliases" subject identifiers sort among the atomic formats by name,
+//! 2. then by the canonical member values, in the order the Identifier Format defines them; the
+//!    canonical identifiers of "aliases" subject identifiers are compared lexicographically,
+//! 3. then, to stay consistent with equality, by the member values as given.
+//!
+//! Identifiers that only differ in case-insensitive parts are therefore adjacent when sorted,
+//! which lets ordered stores range-scan over the canonical form.
+//!
+//! Comparing two "email" or two "account" identifiers canonicalizes both on every comparison,
+//! which parses the address and allocates; the other formats are compared without allocating.
+
+use crate::{Aliases, Atomic, EmailFolding, NormalizationPipeline, Normalizer, SubjectId};
+use std::borrow::Cow;
+use std::cmp::Ordering;
+
+/// [CanonicalPolicy] selects the canonicalization applied by [`SubjectId::canonicalize`].
+///
+/// ```
+/// use subject_id::{Atomic, CanonicalPolicy, CaseFolding, SubjectId};
+/// let sub_id = SubjectId::Atomic(Atomic::Email { email: "John.Doe@Example.COM".to_owned() });
+/// let policy = CanonicalPolicy::default();
+/// let want = SubjectId::Atomic(Atomic::Email { email: "John.Doe@example.com".to_owned() });
+/// assert_eq!(sub_id.canonicalize(&policy), want);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CanonicalPolicy {
+    /// Case folding applied to emails and "acct" URIs.
+    pub case_folding: CaseFolding,
+}
+
+/// [CaseFolding] describes which parts of emails and "acct" URIs are lowercased.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum CaseFolding {
+    /// Keep the case of every part, only lowercasing the "acct" URI scheme.
+    Preserve,
+    /// Lowercase the domain, preserving the case of the local part. This is the spec-safe
+    /// default, since only the domain is case-insensitive by specification.
+    #[default]
+    Domain,
+    /// Lowercase the whole address, for receivers whose mail systems ignore local part case.
+    Full,
+}
+
+impl CanonicalPolicy {
+    /// Canonicalization policy that lowercases emails and "acct" URIs entirely.
+    pub fn full_lowercase() -> Self {
+        Self {
+            case_folding: CaseFolding::Full,
+        }
+    }
+}
+
+impl SubjectId {
+    /// Canonicalize the [SubjectId] according to the given policy. Every identifier of an
+    /// "aliases" subject identifier is canonicalized, see [`Atomic::canonicalize`].
+    pub fn canonicalize(&self, policy: &CanonicalPolicy) -> Self {
+        match self {
+            Self::Atomic(id) => Self::Atomic(id.canonicalize(policy)),
+            Self::Aliases(aliases) => Self::Aliases(aliases.canonicalize(policy)),
+        }
+    }
+}
+
+#[cfg(feature = "uuid")]
+impl SubjectId {
+    /// Derive a deterministic version 5 UUID from the canonical form of the [SubjectId] under the
+    /// default policy, within the given namespace. Identifiers with the same canonical form map to
+    /// the same UUID, which makes it suitable as a primary key for subjects.
+    ///
+    /// ```
+    /// use subject_id::{Atomic, SubjectId};
+    /// use uuid::Uuid;
+    /// let namespace = Uuid::NAMESPACE_URL;
+    /// let a = SubjectId::Atomic(Atomic::Email { email: "user@example.com".to_owned() });
+    /// let b = SubjectId::Atomic(Atomic::Email { email: "user@EXAMPLE.com".to_owned() });
+    /// assert_eq!(a.to_uuid_v5(&namespace), b.to_uuid_v5(&namespace));
+    /// ```
+    pub fn to_uuid_v5(&self, namespace: &uuid::Uuid) -> uuid::Uuid {
+        let canonical = self.canonicalize(&CanonicalPolicy::default());
+        let json = serde_json::to_vec(&canonical).expect("subject identifiers always serialize");
+        uuid::Uuid::new_v5(namespace, &json)
+    }
+}
+
+impl Aliases {
+    /// Canonicalize every identifier of the [Aliases] according to the given policy.
+    pub fn canonicalize(&self, policy: &CanonicalPolicy) -> Self {
+        let identifiers = self
+            .identifiers
+            .iter()
+            .map(|id| id.canonicalize(policy))
+            .collect();
+        Self { identifiers }
+    }
+
+    /// Report the identifiers that are duplicates once canonicalized according to the given
+    /// policy, as pairs of indices like [`Aliases::find_duplicates`].
+    pub fn find_canonical_duplicates(&self, policy: &CanonicalPolicy) -> Vec<(usize, usize)> {
+        crate::duplicates(&self.canonicalize(policy).identifiers)
+    }
+
+    /// Canonicalize the [Aliases] according to the given policy and collapse equivalent
+    /// identifiers into the first of them. Beyond canonical duplicates, an "email" address, an
+    /// "account" URI and a "mailto" "uri" identifier are equivalent when they name the same
+    /// address.
+    ///
+    /// ```
+    /// use subject_id::{Aliases, Atomic, CanonicalPolicy};
+    /// let aliases = Aliases::from(vec![
+    ///     Atomic::Email { email: "user@example.com".to_owned() },
+    ///     Atomic::Account { uri: "acct:user@EXAMPLE.com".to_owned() },
+    ///     Atomic::Uri { uri: "mailto:user@example.com".to_owned() },
+    /// ]);
+    /// let minimal = aliases.canonical_minimal(&CanonicalPolicy::default());
+    /// assert_eq!(minimal.identifiers, vec![Atomic::Email { email: "user@example.com".to_owned() }]);
+    /// ```
+    pub fn canonical_minimal(&self, policy: &CanonicalPolicy) -> Self {
+        let mut keys = Vec::new();
+        let mut identifiers = Vec::new();
+        for id in self.canonicalize(policy).identifiers {
+            let key = equivalence_key(&id, policy);
+            if !keys.contains(&key) {
+                keys.push(key);
+                identifiers.push(id);
+            }
+        }
+        Self { identifiers }
+    }
+}
+
+impl Atomic {
+    /// Canonicalize the atomic identifier according to the given policy, see [`EmailFolding`](crate::EmailFolding).
+    /// Email addresses that parse as an [`EmailAddress`](crate::EmailAddress) are normalized
+    /// (comments and unnecessary quotes are removed) before case folding; other addresses are
+    /// only case folded.
+    ///
+    /// ```
+    /// use subject_id::{Atomic, CanonicalPolicy};
+    /// let sub_id = Atomic::Account { uri: "ACCT:Example.User@Service.Example.COM".to_owned() };
+    /// let got = sub_id.canonicalize(&CanonicalPolicy::full_lowercase());
+    /// let want = Atomic::Account { uri: "acct:example.user@service.example.com".to_owned() };
+    /// assert_eq!(got, want);
+    /// ```
+    pub fn canonicalize(&self, policy: &CanonicalPolicy) -> Self {
+        NormalizationPipeline::from(policy).normalize_atomic(self.clone())
+    }
+}
+
+impl Ord for SubjectId {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (Self::Atomic(a), Self::Atomic(b)) => a.cmp(b),
+            (Self::Aliases(a), Self::Aliases(b)) => a.cmp(b),
+            _ => self.format().cmp(other.format()),
+        }
+    }
+}
+
+impl PartialOrd for SubjectId {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Aliases {
+    fn cmp(&self, other: &Self) -> Ordering {
+        canonical_keys(&self.identifiers)
+            .cmp(&canonical_keys(&other.identifiers))
+            .then_with(|| raw_keys(&self.identifiers).cmp(&raw_keys(&other.identifiers)))
+    }
+}
+
+impl PartialOrd for Aliases {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Atomic {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.format()
+            .cmp(other.format())
+            .then_with(|| canonical_key(self).cmp(&canonical_key(other)))
+            .then_with(|| sort_key(self).1.cmp(&sort_key(other).1))
+    }
+}
+
+impl PartialOrd for Atomic {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Key under which canonical identifiers are equivalent: the address of emails, "acct" URIs and
+/// "mailto" URIs, or the format and member values of other identifiers.
+fn equivalence_key(id: &Atomic, policy: &CanonicalPolicy) -> (&'static str, Vec<String>) {
+    let address = match id {
+        Atomic::Email { email } => Some(email.clone()),
+        Atomic::Account { uri } => uri.strip_prefix("acct:").map(str::to_owned),
+        Atomic::Uri { uri } => match uri.split_once(':') {
+            Some((scheme, rest)) if scheme.eq_ignore_ascii_case("mailto") => {
+                let address = rest.split('?').next().unwrap_or(rest);
+                Some(crate::normalize::fold_address(address, policy.case_folding))
+            }
+            _ => None,
+        },
+        _ => None,
+    };
+    match address {
+        Some(address) => ("address", vec![address]),
+        None => {
+            let (format, values) = sort_key(id);
+            (format, values.into_iter().map(str::to_owned).collect())
+        }
+    }
+}
+
+/// Format name and canonical member values under the default policy. Only emails and "acct"
+/// URIs are changed by canonicalization, the members of other formats are borrowed as given.
+fn canonical_key(id: &Atomic) -> (&'static str, Vec<Cow<'_, str>>) {
+    match id {
+        Atomic::Email { .. } | Atomic::Account { .. } => {
+            let canonical = EmailFolding::default().normalize(id.clone());
+            let (format, values) = sort_key(&canonical);
+            let values = values
+                .into_iter()
+                .map(|v| Cow::Owned(v.to_owned()))
+                .collect();
+            (format, values)
+        }
+        _ => {
+            let (format, values) = sort_key(id);
+            (format, values.into_iter().map(Cow::Borrowed).collect())
+        }
+    }
+}
+
+/// Canonical key of every identifier, see [canonical_key].
+fn canonical_keys(ids: &[Atomic]) -> Vec<(&'static str, Vec<Cow<'_, str>>)> {
+    ids.iter().map(canonical_key).collect()
+}
+
+/// Member values of every identifier, as given.
+fn raw_keys(ids: &[Atomic]) -> Vec<Vec<&str>> {
+    ids.iter().map(|id| sort_key(id).1).collect()
+}
+
+/// Format name and member values, compared in this order.
+fn sort_key(id: &Atomic) -> (&'static str, Vec<&str>) {
+    let values = id.members().into_iter().map(|(_, value)| value).collect();
+    (id.format(), values)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_canonicalize_email() {
+        let email = |email: &str| Atomic::Email {
+            email: email.to_owned(),
+        };
+        let input = email("(hr) \"John.Doe\"@Example.COM");
+        let cases = [
+            (CaseFolding::Preserve, "John.Doe@Example.COM"),
+            (CaseFolding::Domain, "John.Doe@example.com"),
+            (CaseFolding::Full, "john.doe@example.com"),
+        ];
+        for (case_folding, want) in cases {
+            let policy = CanonicalPolicy { case_folding };
+            assert_eq!(input.canonicalize(&policy), email(want), "{case_folding:?}");
+        }
+    }
+
+    #[test]
+    fn test_canonicalize_account() {
+        let sub_id = SubjectId::Aliases(Aliases {
+            identifiers: vec![
+                Atomic::Account {
+                    uri: "Acct:Example.User@Service.Example.COM".to_owned(),
+                },
+                Atomic::Opaque {
+                    id: "AbC".to_owned(),
+                },
+            ],
+        });
+        let want = SubjectId::Aliases(Aliases {
+            identifiers: vec![
+                Atomic::Account {
+                    uri: "acct:Example.User@service.example.com".to_owned(),
+                },
+                Atomic::Opaque {
+                    id: "AbC".to_owned(),
+                },
+            ],
+        });
+        assert_eq!(sub_id.canonicalize(&CanonicalPolicy::default()), want);
+    }
+
+    #[test]
+    fn test_canonical_minimal() {
+        let aliases = Aliases::from(vec![
+            Atomic::Uri {
+                uri: "MAILTO:John.Doe@Example.COM?subject=hi".to_owned(),
+            },
+            Atomic::Opaque { id: "1".to_owned() },
+            Atomic::Email {
+                email: "John.Doe@example.com".to_owned(),
+            },
+            Atomic::Email {
+                email: "john.doe@example.com".to_owned(),
+            },
+            Atomic::Opaque { id: "1".to_owned() },
+        ]);
+        let got = aliases.canonical_minimal(&CanonicalPolicy::default());
+        let want = vec![
+            Atomic::Uri {
+                uri: "MAILTO:John.Doe@Example.COM?subject=hi".to_owned(),
+            },
+            Atomic::Opaque { id: "1".to_owned() },
+            Atomic::Email {
+                email: "john.doe@example.com".to_owned(),
+            },
+        ];
+        assert_eq!(got.identifiers, want);
+        let got = aliases.canonical_minimal(&CanonicalPolicy::full_lowercase());
+        assert_eq!(got.identifiers.len(), 2);
+    }
+
+    #[test]
+    fn test_order() {
+        let email = |email: &str| {
+            SubjectId::Atomic(Atomic::Email {
+                email: email.to_owned(),
+            })
+        };
+        let mut got = vec![
+            SubjectId::Atomic(Atomic::Opaque { id: "1".to_owned() }),
+            email("b@example.com"),
+            SubjectId::Aliases(Aliases::from(vec![Atomic::Opaque { id: "1".to_owned() }])),
+            email("a@example.com"),
+            email("a@Example.com"),
+        ];
+        got.sort();
+        let want = vec![
+            SubjectId::Aliases(Aliases::from(vec![Atomic::Opaque { id: "1".to_owned() }])),
+            email("a@Example.com"),
+            email("a@example.com"),
+            email("b@example.com"),
+            SubjectId::Atomic(Atomic::Opaque { id: "1".to_owned() }),
+        ];
+        assert_eq!(got, want);
+
+        let aliases = |email: &str, id: &str| {
+            SubjectId::Aliases(Aliases::from(vec![
+                Atomic::Email {
+                    email: email.to_owned(),
+                },
+                Atomic::Opaque { id: id.to_owned() },
+            ]))
+        };
+        let mut got = vec![
+            aliases("a@Example.com", "z"),
+            aliases("a@example.com", "b"),
+            aliases("a@Example.com", "b"),
+        ];
+        got.sort();
+        let want = vec![
+            aliases("a@Example.com", "b"),
+            aliases("a@example.com", "b"),
+            aliases("a@Example.com", "z"),
+        ];
+        assert_eq!(got, want);
+    }
+
+    #[cfg(feature = "uuid")]
+    #[test]
+    fn test_to_uuid_v5() {
+        let namespace = uuid::Uuid::NAMESPACE_OID;
+        let sub_id = SubjectId::Atomic(Atomic::Opaque {
+            id: "1i3j4l".to_owned(),
+        });
+        let got = sub_id.to_uuid_v5(&namespace);
+        assert_eq!(got.get_version_num(), 5);
+        assert_eq!(got, sub_id.to_uuid_v5(&namespace));
+        assert_ne!(got, sub_id.to_uuid_v5(&uuid::Uuid::NAMESPACE_URL));
+    }
+}
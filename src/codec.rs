@@ -0,0 +1,228 @@
+//! `codec` module frames a byte stream of concatenated or newline-delimited subject identifier
+//! JSON objects, for receivers consuming long-lived event streams with [`FramedRead`]. The
+//! decoder scans incrementally, resuming where the previous call stopped, and rejects objects
+//! larger than its size limit before buffering them entirely. Backpressure is left to the framed
+//! reader, which only reads further bytes when the next item is polled.
+//!
+//! [`FramedRead`]: tokio_util::codec::FramedRead
+
+use crate::{Error, SubjectId};
+use tokio_util::bytes::{Buf, BytesMut};
+use tokio_util::codec::Decoder;
+
+/// [SubjectIdCodec] decodes subject identifiers from a byte stream. Objects may be separated by
+/// whitespace or commas. After an [`Error::FrameTooLarge`], the rest of the oversized object is
+/// discarded, and after an [`Error::InvalidFrame`], the bytes up to the next `{` are discarded;
+/// decoding then resumes with the next object.
+///
+/// ```
+/// use subject_id::{SubjectId, SubjectIdCodec};
+/// use tokio_util::bytes::BytesMut;
+/// use tokio_util::codec::Decoder;
+/// let mut codec = SubjectIdCodec::default();
+/// let mut buf = BytesMut::from(&br#"{"format":"opaque","id":"1i3j4l"} {"format":"#[..]);
+/// let sub_id = codec.decode(&mut buf).unwrap().unwrap();
+/// assert_eq!(sub_id.format(), SubjectId::FORMAT_OPAQUE);
+/// assert!(codec.decode(&mut buf).unwrap().is_none());
+/// ```
+#[derive(Clone, Debug)]
+pub struct SubjectIdCodec {
+    max_len: usize,
+    scanned: usize,
+    depth: usize,
+    in_string: bool,
+    escaped: bool,
+    discarding: bool,
+}
+
+/// Result of scanning the buffered bytes for the end of an object.
+enum Scan {
+    /// The object ends at the given index.
+    End(usize),
+    /// The object does not end within the buffered bytes.
+    Partial,
+    /// The object exceeds the size limit at the given index.
+    TooLarge(usize),
+}
+
+impl SubjectIdCodec {
+    /// Default size limit of a single subject identifier, in bytes.
+    pub const DEFAULT_MAX_LEN: usize = 64 * 1024;
+
+    /// Codec rejecting subject identifiers longer than `max_len` bytes with
+    /// [`Error::FrameTooLarge`].
+    pub fn with_max_len(max_len: usize) -> Self {
+        Self {
+            max_len,
+            scanned: 0,
+            depth: 0,
+            in_string: false,
+            escaped: false,
+            discarding: false,
+        }
+    }
+
+    /// The size limit of a single subject identifier, in bytes.
+    pub fn max_len(&self) -> usize {
+        self.max_len
+    }
+
+    /// Drop the separators preceding the next object.
+    fn skip_separators(&mut self, buf: &mut BytesMut) {
+        let n = buf
+            .iter()
+            .take_while(|b| b.is_ascii_whitespace() || **b == b',')
+            .count();
+        buf.advance(n);
+    }
+
+    /// Scan the buffer from where the previous call stopped. Once the object ends, the scan
+    /// state is back to its initial state.
+    fn scan(&mut self, buf: &[u8], limit: usize) -> Scan {
+        for (i, &b) in buf.iter().enumerate().skip(self.scanned) {
+            if i >= limit {
+                return Scan::TooLarge(i);
+            }
+            match (self.in_string, b) {
+                (true, _) if self.escaped => self.escaped = false,
+                (true, b'\\') => self.escaped = true,
+                (true, b'"') => self.in_string = false,
+                (true, _) => {}
+                (false, b'"') => self.in_string = true,
+                (false, b'{' | b'[') => self.depth += 1,
+                (false, b'}' | b']') => {
+                    self.depth = self.depth.saturating_sub(1);
+                    if self.depth == 0 {
+                        self.scanned = 0;
+                        return Scan::End(i);
+                    }
+                }
+                (false, _) => {}
+            }
+        }
+        self.scanned = buf.len();
+        Scan::Partial
+    }
+}
+
+impl Default for SubjectIdCodec {
+    fn default() -> Self {
+        Self::with_max_len(Self::DEFAULT_MAX_LEN)
+    }
+}
+
+impl Decoder for SubjectIdCodec {
+    type Item = SubjectId;
+    type Error = Error;
+
+    fn decode(&mut self, buf: &mut BytesMut) -> Result<Option<SubjectId>, Error> {
+        if self.discarding {
+            match self.scan(buf, usize::MAX) {
+                Scan::End(end) => {
+                    buf.advance(end + 1);
+                    self.discarding = false;
+                }
+                _ => {
+                    buf.clear();
+                    self.scanned = 0;
+                    return Ok(None);
+                }
+            }
+        }
+        if self.scanned == 0 {
+            self.skip_separators(buf);
+            match buf.first() {
+                None => return Ok(None),
+                Some(b'{') => {}
+                Some(&b) => {
+                    let skip = buf.iter().position(|&c| c == b'{').unwrap_or(buf.len());
+                    buf.advance(skip);
+                    return Err(Error::InvalidFrame(b));
+                }
+            }
+        }
+        match self.scan(buf, self.max_len) {
+            Scan::End(end) => {
+                let frame = buf.split_to(end + 1);
+                Ok(Some(serde_json::from_slice(&frame)?))
+            }
+            Scan::Partial => Ok(None),
+            Scan::TooLarge(at) => {
+                buf.advance(at);
+                self.scanned = 0;
+                self.discarding = true;
+                Err(Error::FrameTooLarge(self.max_len))
+            }
+        }
+    }
+
+    fn decode_eof(&mut self, buf: &mut BytesMut) -> Result<Option<SubjectId>, Error> {
+        match self.decode(buf)? {
+            Some(sub_id) => Ok(Some(sub_id)),
+            None if buf.is_empty() => Ok(None),
+            None => Err(std::io::Error::new(
+                std::io::ErrorKind::UnexpectedEof,
+                "truncated subject identifier",
+            )
+            .into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_decode_incremental() {
+        let input = br#"{"format":"email","email":"a}\"{@example.com"},
+            {"format":"aliases","identifiers":[{"format":"opaque","id":"1"}]}
+        "#;
+        let mut codec = SubjectIdCodec::default();
+        let mut buf = BytesMut::new();
+        let mut got = Vec::new();
+        for chunk in input.chunks(7) {
+            buf.extend_from_slice(chunk);
+            while let Some(sub_id) = codec.decode(&mut buf).unwrap() {
+                got.push(sub_id.format());
+            }
+        }
+        assert!(codec.decode_eof(&mut buf).unwrap().is_none());
+        assert_eq!(got, [SubjectId::FORMAT_EMAIL, SubjectId::FORMAT_ALIASES]);
+    }
+
+    #[test]
+    fn test_decode_errors() {
+        let mut codec = SubjectIdCodec::with_max_len(16);
+        let mut buf = BytesMut::from(&br#"{"format":"opaque","id""#[..]);
+        assert!(matches!(
+            codec.decode(&mut buf),
+            Err(Error::FrameTooLarge(16))
+        ));
+
+        let mut codec = SubjectIdCodec::with_max_len(40);
+        let mut buf = BytesMut::from(&br#"{"format":"opaque","id":"}"#[..]);
+        assert!(codec.decode(&mut buf).unwrap().is_none());
+        buf.extend_from_slice(br#"{[0123456789abcdef0123456789"} {"format":"opaque","id":"1"}"#);
+        assert!(matches!(
+            codec.decode(&mut buf),
+            Err(Error::FrameTooLarge(40))
+        ));
+        let sub_id = codec.decode(&mut buf).unwrap().unwrap();
+        assert_eq!(sub_id.format(), SubjectId::FORMAT_OPAQUE);
+        assert!(codec.decode_eof(&mut buf).unwrap().is_none());
+
+        let mut codec = SubjectIdCodec::default();
+        let mut buf = BytesMut::from(&br#"[] x{"format":"opaque","id":"1"}"#[..]);
+        assert!(matches!(
+            codec.decode(&mut buf),
+            Err(Error::InvalidFrame(b'['))
+        ));
+        let sub_id = codec.decode(&mut buf).unwrap().unwrap();
+        assert_eq!(sub_id.format(), SubjectId::FORMAT_OPAQUE);
+        assert!(codec.decode_eof(&mut buf).unwrap().is_none());
+
+        let mut buf = BytesMut::from(&br#"{"format":"#[..]);
+        assert!(matches!(codec.decode_eof(&mut buf), Err(Error::Io(..))));
+    }
+}
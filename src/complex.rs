@@ -0,0 +1,422 @@
+//! `complex` module defines complex subjects, as used by the Shared Signals Framework and the
+//! Continuous Access Evaluation Profile (CAEP). A complex subject describes a subject made of
+//! several components, e.g. a user on a particular device, where each component is identified
+//! with a subject identifier of its own.
+//!
+//! See: [`SSF`]
+//!
+//! [`SSF`]: https://openid.net/specs/openid-sharedsignals-framework-1_0.html
+
+use crate::{Atomic, Error, ValidationPolicy};
+use serde::{Deserialize, Serialize};
+
+/// [ComplexSubject] is a subject made of several components. Every component is OPTIONAL, but
+/// at least one component MUST be present. A complex subject serializes with the "format" member
+/// "complex", which deserialization requires along with at least one component.
+///
+/// ```
+/// use subject_id::{Atomic, ComplexSubject};
+/// let subject = ComplexSubject::user_and_device(
+///     Atomic::Email { email: "user@example.com".to_owned() },
+///     Atomic::Opaque { id: "device-1i3j4l".to_owned() },
+/// )
+/// .with_tenant(Atomic::Opaque { id: "tenant-42".to_owned() });
+/// println!("{}", serde_json::to_string(&subject).unwrap());
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "format", rename = "complex", try_from = "ComplexSubjectRepr")]
+pub struct ComplexSubject {
+    /// The user the subject refers to.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user: Option<Atomic>,
+    /// The device the user is using.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub device: Option<Atomic>,
+    /// The session of the user, typically identified with an opaque session ID.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session: Option<Atomic>,
+    /// The application the subject refers to.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub application: Option<Atomic>,
+    /// The tenant the subject belongs to.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tenant: Option<Atomic>,
+    /// The organizational unit the subject belongs to.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub org_unit: Option<Atomic>,
+    /// The group the subject belongs to.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group: Option<Atomic>,
+}
+
+/// Deserialized form of [ComplexSubject], before checking that a component is present.
+#[derive(Deserialize)]
+struct ComplexSubjectRepr {
+    #[allow(unused)]
+    format: ComplexFormat,
+    #[serde(default)]
+    user: Option<Atomic>,
+    #[serde(default)]
+    device: Option<Atomic>,
+    #[serde(default)]
+    session: Option<Atomic>,
+    #[serde(default)]
+    application: Option<Atomic>,
+    #[serde(default)]
+    tenant: Option<Atomic>,
+    #[serde(default)]
+    org_unit: Option<Atomic>,
+    #[serde(default)]
+    group: Option<Atomic>,
+}
+
+/// The "format" member of complex subjects.
+#[derive(Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum ComplexFormat {
+    Complex,
+}
+
+impl TryFrom<ComplexSubjectRepr> for ComplexSubject {
+    type Error = Error;
+
+    fn try_from(repr: ComplexSubjectRepr) -> Result<Self, Error> {
+        let subject = Self {
+            user: repr.user,
+            device: repr.device,
+            session: repr.session,
+            application: repr.application,
+            tenant: repr.tenant,
+            org_unit: repr.org_unit,
+            group: repr.group,
+        };
+        match subject.components().is_empty() {
+            true => Err(Error::EmptyComplexSubject),
+            false => Ok(subject),
+        }
+    }
+}
+
+/// [ComplexSubjectPolicy] selects the rules enforced by [`ComplexSubject::validate`].
+#[derive(Clone, Debug, Default)]
+pub struct ComplexSubjectPolicy {
+    /// Require a user component whenever a session component is present, since a session is
+    /// meaningless to most receivers without the user it belongs to.
+    pub session_requires_user: bool,
+    /// Rules every component is validated against, see [`Atomic::validate_with`].
+    pub components: ValidationPolicy,
+}
+
+impl ComplexSubject {
+    /// Complex subject referring to a user only.
+    pub fn user(user: Atomic) -> Self {
+        Self {
+            user: Some(user),
+            ..Self::default()
+        }
+    }
+
+    /// Complex subject referring to a user on a device, the shape of CAEP device-compliance and
+    /// session events.
+    pub fn user_and_device(user: Atomic, device: Atomic) -> Self {
+        Self {
+            device: Some(device),
+            ..Self::user(user)
+        }
+    }
+
+    /// Set the session component.
+    pub fn with_session(self, session: Atomic) -> Self {
+        Self {
+            session: Some(session),
+            ..self
+        }
+    }
+
+    /// Set the session component to an opaque session ID.
+    ///
+    /// ```
+    /// use subject_id::{Atomic, ComplexSubject};
+    /// let subject = ComplexSubject::user(Atomic::Email { email: "user@example.com".to_owned() })
+    ///     .with_session_id("s-1i3j4l");
+    /// assert_eq!(subject.session_id(), Some("s-1i3j4l"));
+    /// ```
+    pub fn with_session_id(self, id: impl Into<String>) -> Self {
+        self.with_session(Atomic::Opaque { id: id.into() })
+    }
+
+    /// The opaque session ID of the session component, if the session is identified with one.
+    pub fn session_id(&self) -> Option<&str> {
+        match &self.session {
+            Some(Atomic::Opaque { id }) => Some(id),
+            _ => None,
+        }
+    }
+
+    /// Start building a complex subject, see [ComplexSubjectBuilder].
+    pub fn builder() -> ComplexSubjectBuilder {
+        ComplexSubjectBuilder::default()
+    }
+
+    /// Components of the complex subject that are present, paired with their member names.
+    pub fn components(&self) -> Vec<(&'static str, &Atomic)> {
+        [
+            ("user", &self.user),
+            ("device", &self.device),
+            ("session", &self.session),
+            ("application", &self.application),
+            ("tenant", &self.tenant),
+            ("org_unit", &self.org_unit),
+            ("group", &self.group),
+        ]
+        .into_iter()
+        .filter_map(|(name, component)| component.as_ref().map(|id| (name, id)))
+        .collect()
+    }
+
+    /// Validate the complex subject against the given policy: at least one component MUST be
+    /// present, every component MUST be a valid atomic identifier under the component rules of
+    /// the policy (see [`Atomic::validate_with`]), and the rules selected by the policy MUST hold.
+    ///
+    /// ```
+    /// use subject_id::{ComplexSubject, ComplexSubjectPolicy};
+    /// let subject = ComplexSubject::default().with_session_id("s-1i3j4l");
+    /// let policy = ComplexSubjectPolicy { session_requires_user: true, ..Default::default() };
+    /// assert!(subject.validate(&policy).is_err());
+    /// ```
+    pub fn validate(&self, policy: &ComplexSubjectPolicy) -> Result<(), Error> {
+        let components = self.components();
+        if components.is_empty() {
+            return Err(Error::EmptyComplexSubject);
+        }
+        components
+            .iter()
+            .try_for_each(|(_, id)| id.validate_with(&policy.components))?;
+        if policy.session_requires_user && self.session.is_some() && self.user.is_none() {
+            return Err(Error::SessionWithoutUser);
+        }
+        Ok(())
+    }
+
+    /// Set the tenant component.
+    pub fn with_tenant(self, tenant: Atomic) -> Self {
+        Self {
+            tenant: Some(tenant),
+            ..self
+        }
+    }
+
+    /// Set the application component.
+    pub fn with_application(self, application: Atomic) -> Self {
+        Self {
+            application: Some(application),
+            ..self
+        }
+    }
+
+    /// Set the organizational unit component.
+    pub fn with_org_unit(self, org_unit: Atomic) -> Self {
+        Self {
+            org_unit: Some(org_unit),
+            ..self
+        }
+    }
+
+    /// Set the group component.
+    pub fn with_group(self, group: Atomic) -> Self {
+        Self {
+            group: Some(group),
+            ..self
+        }
+    }
+}
+
+/// [ComplexSubjectBuilder] builds a [ComplexSubject] that is guaranteed to be valid: it has at
+/// least one component, and every component is a valid atomic identifier. Components cannot be
+/// complex subjects themselves, so complex subjects are never nested.
+///
+/// ```
+/// use subject_id::{Atomic, ComplexSubject};
+/// let subject = ComplexSubject::builder()
+///     .user(Atomic::Email { email: "user@example.com".to_owned() })
+///     .session(Atomic::Opaque { id: "s-1i3j4l".to_owned() })
+///     .build()
+///     .unwrap();
+/// assert_eq!(subject.session_id(), Some("s-1i3j4l"));
+/// assert!(ComplexSubject::builder().build().is_err());
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct ComplexSubjectBuilder {
+    subject: ComplexSubject,
+    policy: ComplexSubjectPolicy,
+}
+
+impl ComplexSubjectBuilder {
+    /// Set the user component.
+    pub fn user(mut self, user: Atomic) -> Self {
+        self.subject.user = Some(user);
+        self
+    }
+
+    /// Set the device component.
+    pub fn device(mut self, device: Atomic) -> Self {
+        self.subject.device = Some(device);
+        self
+    }
+
+    /// Set the session component.
+    pub fn session(mut self, session: Atomic) -> Self {
+        self.subject.session = Some(session);
+        self
+    }
+
+    /// Set the application component.
+    pub fn application(mut self, application: Atomic) -> Self {
+        self.subject.application = Some(application);
+        self
+    }
+
+    /// Set the tenant component.
+    pub fn tenant(mut self, tenant: Atomic) -> Self {
+        self.subject.tenant = Some(tenant);
+        self
+    }
+
+    /// Set the organizational unit component.
+    pub fn org_unit(mut self, org_unit: Atomic) -> Self {
+        self.subject.org_unit = Some(org_unit);
+        self
+    }
+
+    /// Set the group component.
+    pub fn group(mut self, group: Atomic) -> Self {
+        self.subject.group = Some(group);
+        self
+    }
+
+    /// Set the policy the complex subject is validated against when built.
+    pub fn policy(mut self, policy: ComplexSubjectPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Validate and return the complex subject, see [`ComplexSubject::validate`].
+    pub fn build(self) -> Result<ComplexSubject, Error> {
+        self.subject.validate(&self.policy)?;
+        Ok(self.subject)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_user_and_device() {
+        let subject = ComplexSubject::user_and_device(
+            Atomic::IssuerSubject {
+                issuer: "https://issuer.example.com/".to_owned(),
+                subject: "145234573".to_owned(),
+            },
+            Atomic::Opaque {
+                id: "device-1".to_owned(),
+            },
+        )
+        .with_tenant(Atomic::Opaque {
+            id: "tenant-42".to_owned(),
+        });
+        let want = json!({
+            "format": "complex",
+            "user": {
+                "format": "iss_sub",
+                "issuer": "https://issuer.example.com/",
+                "subject": "145234573",
+            },
+            "device": { "format": "opaque", "id": "device-1" },
+            "tenant": { "format": "opaque", "id": "tenant-42" },
+        });
+        assert_eq!(serde_json::to_value(&subject).unwrap(), want);
+        assert_eq!(
+            serde_json::from_value::<ComplexSubject>(want).unwrap(),
+            subject
+        );
+        let invalid = [
+            json!({ "format": "complex" }),
+            json!({ "device": { "format": "opaque", "id": "device-1" } }),
+            json!({ "format": "opaque", "device": { "format": "opaque", "id": "device-1" } }),
+        ];
+        for json in invalid {
+            assert!(serde_json::from_value::<ComplexSubject>(json).is_err());
+        }
+    }
+
+    #[test]
+    fn test_session() {
+        let policy = ComplexSubjectPolicy {
+            session_requires_user: true,
+            ..Default::default()
+        };
+        let session = ComplexSubject::default().with_session_id("s-1");
+        assert_eq!(session.session_id(), Some("s-1"));
+        assert!(session.validate(&ComplexSubjectPolicy::default()).is_ok());
+        assert!(matches!(
+            session.validate(&policy),
+            Err(Error::SessionWithoutUser)
+        ));
+        let subject = ComplexSubject::user(Atomic::Email {
+            email: "user@example.com".to_owned(),
+        })
+        .with_session_id("s-1");
+        assert!(subject.validate(&policy).is_ok());
+        let policy = ComplexSubjectPolicy {
+            components: ValidationPolicy {
+                opaque: crate::OpaquePolicy::uuid(),
+                ..Default::default()
+            },
+            ..policy
+        };
+        assert!(matches!(
+            subject.validate(&policy),
+            Err(Error::InvalidSubject {
+                format: "opaque",
+                ..
+            })
+        ));
+        let json = serde_json::to_value(&subject).unwrap();
+        assert_eq!(json["session"], json!({ "format": "opaque", "id": "s-1" }));
+    }
+
+    #[test]
+    fn test_builder() {
+        let got = ComplexSubject::builder()
+            .device(Atomic::Opaque {
+                id: "device-1".to_owned(),
+            })
+            .tenant(Atomic::Opaque {
+                id: "tenant-42".to_owned(),
+            })
+            .build()
+            .unwrap();
+        let names: Vec<_> = got.components().into_iter().map(|(n, _)| n).collect();
+        assert_eq!(names, ["device", "tenant"]);
+        let got = ComplexSubject::builder().build();
+        assert!(matches!(got, Err(Error::EmptyComplexSubject)));
+        let got = ComplexSubject::builder()
+            .user(Atomic::Email {
+                email: "not an email".to_owned(),
+            })
+            .build();
+        assert!(matches!(got, Err(Error::InvalidSubject { .. })));
+        let got = ComplexSubject::builder()
+            .session(Atomic::Opaque {
+                id: "s-1".to_owned(),
+            })
+            .policy(ComplexSubjectPolicy {
+                session_requires_user: true,
+                ..Default::default()
+            })
+            .build();
+        assert!(matches!(got, Err(Error::SessionWithoutUser)));
+    }
+}
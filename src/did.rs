@@ -0,0 +1,228 @@
+//! `did` module resolves the DID of "did" identifiers to their DID document, so that relying
+//! parties can check that a DID subject exists before acting on it. Resolution is pluggable
+//! through the [DidResolver] trait; with the `did-web` feature, [DidWebResolver] resolves the
+//! "did:web" method over HTTPS.
+
+use crate::{Atomic, Error};
+use serde_json::Value;
+use std::future::Future;
+
+/// [DidResolver] resolves a DID (without path, query or fragment) to its DID document.
+pub trait DidResolver {
+    /// Fetch the DID document of the DID.
+    fn resolve(&self, did: &str) -> impl Future<Output = Result<Value, Error>> + Send;
+}
+
+impl Atomic {
+    /// Resolve the DID of a "did" identifier with the resolver, checking that the "id" member
+    /// of the returned DID document is the DID. Path, query and fragment of the DID URL are
+    /// ignored.
+    pub async fn resolve_did(&self, resolver: &impl DidResolver) -> Result<Value, Error> {
+        let Atomic::Did { url } = self else {
+            return Err(Error::InvalidSubject {
+                format: self.format(),
+                reason: "not a \"did\" identifier".to_owned(),
+            });
+        };
+        let did = url.split(['/', '?', '#']).next().unwrap_or(url);
+        let document = resolver.resolve(did).await?;
+        match document.get("id").and_then(Value::as_str) {
+            Some(id) if id == did => Ok(document),
+            Some(id) => Err(Error::DidResolution(format!("document is for {id:?}"))),
+            None => Err(Error::DidResolution("document has no \"id\"".to_owned())),
+        }
+    }
+}
+
+/// [DidWebResolver] resolves "did:web" DIDs by fetching `did.json` from the web domain, as
+/// specified by the [`did:web`] method.
+///
+/// Only domains that are DNS names with at least two labels are fetched: IP literals,
+/// "localhost" and domains containing URL delimiters are rejected, so that DID subjects cannot
+/// point the request at internal endpoints. DID documents larger than [`MAX_DID_DOCUMENT_LEN`]
+/// are rejected. The default client does not follow redirects; a client passed to
+/// [`DidWebResolver::with_client`] must not follow redirects to other hosts either.
+///
+/// [`did:web`]: https://w3c-ccg.github.io/did-method-web/
+/// [`MAX_DID_DOCUMENT_LEN`]: DidWebResolver::MAX_DID_DOCUMENT_LEN
+#[cfg(feature = "did-web")]
+#[derive(Clone, Debug)]
+pub struct DidWebResolver {
+    client: reqwest::Client,
+}
+
+#[cfg(feature = "did-web")]
+impl DidWebResolver {
+    /// Size limit of a DID document, in bytes.
+    pub const MAX_DID_DOCUMENT_LEN: usize = 64 * 1024;
+
+    /// Resolver using the given HTTP client, e.g. to configure timeouts.
+    pub fn with_client(client: reqwest::Client) -> Self {
+        Self { client }
+    }
+}
+
+#[cfg(feature = "did-web")]
+impl Default for DidWebResolver {
+    fn default() -> Self {
+        let client = reqwest::Client::builder()
+            .redirect(reqwest::redirect::Policy::none())
+            .build()
+            .expect("default HTTP client builds");
+        Self { client }
+    }
+}
+
+#[cfg(feature = "did-web")]
+impl DidResolver for DidWebResolver {
+    async fn resolve(&self, did: &str) -> Result<Value, Error> {
+        let url = did_web_url(did)?;
+        let response = self.client.get(url).send().await?.error_for_status()?;
+        let body = crate::http::read_body(response, Self::MAX_DID_DOCUMENT_LEN).await?;
+        Ok(serde_json::from_slice(&body)?)
+    }
+}
+
+/// The URL of the DID document of a "did:web" DID: the method-specific identifier is a domain,
+/// with '%3A' encoding a port, optionally followed by ':'-separated, percent-encoded path
+/// segments.
+#[cfg(feature = "did-web")]
+fn did_web_url(did: &str) -> Result<String, Error> {
+    let Some(id) = did.strip_prefix("did:web:") else {
+        let method = did.split(':').nth(1).unwrap_or_default();
+        return Err(Error::UnsupportedDidMethod(method.to_owned()));
+    };
+    let resolution = |reason: &str| Error::DidResolution(format!("{reason} in {did:?}"));
+    let mut segments = id.split(':');
+    let domain = percent_decode(segments.next().unwrap_or_default())
+        .ok_or_else(|| resolution("invalid percent-encoding"))?;
+    let (host, port) = match domain.split_once(':') {
+        Some((host, port)) => (host, Some(port)),
+        None => (domain.as_str(), None),
+    };
+    if !crate::http::is_dns_name(host) {
+        return Err(resolution("domain is not a public DNS name"));
+    }
+    let mut url = reqwest::Url::parse(&format!("https://{host}/"))
+        .map_err(|_| resolution("invalid domain"))?;
+    if let Some(port) = port {
+        let port = port.parse().map_err(|_| resolution("invalid port"))?;
+        url.set_port(Some(port))
+            .map_err(|_| resolution("invalid port"))?;
+    }
+    let path = segments
+        .map(|segment| match percent_decode(segment) {
+            Some(segment) if !matches!(segment.as_str(), "" | "." | "..") => Ok(segment),
+            _ => Err(resolution("invalid path segment")),
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+    {
+        let mut url_path = url.path_segments_mut().expect("https URLs have a path");
+        url_path.clear();
+        match path.is_empty() {
+            true => url_path.extend([".well-known", "did.json"]),
+            false => url_path.extend(&path).push("did.json"),
+        };
+    }
+    Ok(url.into())
+}
+
+/// Decode the `%XX` escapes of the string, failing on malformed escapes or non-UTF-8 results.
+#[cfg(feature = "did-web")]
+fn percent_decode(s: &str) -> Option<String> {
+    let mut bytes = Vec::with_capacity(s.len());
+    let mut rest = s.as_bytes();
+    while let Some((&b, tail)) = rest.split_first() {
+        if b == b'%' {
+            let hex = std::str::from_utf8(tail.get(..2)?).ok()?;
+            bytes.push(u8::from_str_radix(hex, 16).ok()?);
+            rest = &tail[2..];
+        } else {
+            bytes.push(b);
+            rest = tail;
+        }
+    }
+    String::from_utf8(bytes).ok()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json::json;
+
+    struct Static(Value);
+
+    impl DidResolver for Static {
+        async fn resolve(&self, _: &str) -> Result<Value, Error> {
+            Ok(self.0.clone())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_resolve_did() {
+        let resolver = Static(json!({"id": "did:example:123456"}));
+        let id = Atomic::Did {
+            url: "did:example:123456/path#key-1".to_owned(),
+        };
+        assert!(id.resolve_did(&resolver).await.is_ok());
+        let id = Atomic::Did {
+            url: "did:example:654321".to_owned(),
+        };
+        let got = id.resolve_did(&resolver).await;
+        assert!(matches!(got, Err(Error::DidResolution(..))));
+        let id = Atomic::Opaque {
+            id: "did:example:123456".to_owned(),
+        };
+        assert!(id.resolve_did(&resolver).await.is_err());
+    }
+
+    #[cfg(feature = "did-web")]
+    #[test]
+    fn test_did_web_url() {
+        let cases = [
+            (
+                "did:web:w3c-ccg.github.io",
+                "https://w3c-ccg.github.io/.well-known/did.json",
+            ),
+            (
+                "did:web:w3c-ccg.github.io:user:alice",
+                "https://w3c-ccg.github.io/user/alice/did.json",
+            ),
+            (
+                "did:web:example.com%3A3000",
+                "https://example.com:3000/.well-known/did.json",
+            ),
+        ];
+        for (did, want) in cases {
+            assert_eq!(did_web_url(did).unwrap(), want);
+        }
+        let got = did_web_url("did:key:z6Mk");
+        assert!(matches!(got, Err(Error::UnsupportedDidMethod(m)) if m == "key"));
+    }
+
+    #[cfg(feature = "did-web")]
+    #[test]
+    fn test_did_web_url_hostile_host() {
+        let hostile = [
+            "did:web:127.0.0.1",
+            "did:web:localhost",
+            "did:web:internal.localhost",
+            "did:web:a@internal",
+            "did:web:intranet",
+            "did:web:%5B%3A%3A1%5D",
+            "did:web:example.com%2Fadmin",
+            "did:web:example.com%3Ahttp",
+            "did:web:example.com:..:admin",
+            "did:web:example.com:a%2",
+            "did:web:",
+        ];
+        for did in hostile {
+            assert!(
+                matches!(did_web_url(did), Err(Error::DidResolution(..))),
+                "{did}"
+            );
+        }
+        let got = did_web_url("did:web:example.com:user%20name:a%2Fb").unwrap();
+        assert_eq!(got, "https://example.com/user%20name/a%2Fb/did.json");
+    }
+}
@@ -12,7 +12,7 @@ use serde::{de, Deserialize, Deserializer, Serialize};
 /// part and an identifier part.
 ///
 /// [`E.164`]: https://www.itu.int/rec/T-REC-E.164-201011-I/en
-#[derive(Debug, Clone, Serialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
 #[serde(transparent)]
 pub struct PhoneNumber {
     number: String,
@@ -24,12 +24,30 @@ static RE_PHONE: Lazy<Regex> = Lazy::new(|| Regex::new(r"^\+?(\d{1,15})$").unwra
 /// Phone number parsing rules
 impl PhoneNumber {
     fn parse(s: &str) -> Result<Self, Error> {
+        Self::parse_into(String::new(), s).map_err(|(_, err)| err)
+    }
+
+    /// Parse the phone number into the given buffer, reusing its allocation. The buffer is handed
+    /// back along with the error if the phone number is invalid.
+    pub(crate) fn parse_into(mut number: String, s: &str) -> Result<Self, (String, Error)> {
         let Some(caps) = RE_PHONE.captures(s) else {
-            return Err(Error::InvalidPhoneNumber);
+            return Err((number, Error::InvalidPhoneNumber));
         };
-        let number = "+".to_owned() + &caps[1];
+        number.clear();
+        number.push('+');
+        number.push_str(&caps[1]);
         Ok(Self { number })
     }
+
+    /// The buffer holding the phone number.
+    pub(crate) fn into_string(self) -> String {
+        self.number
+    }
+
+    /// The phone number in its normalized form, always starting with '+'.
+    pub fn as_str(&self) -> &str {
+        &self.number
+    }
 }
 
 impl FromStr for PhoneNumber {
@@ -0,0 +1,327 @@
+//! `email` module parses email addresses formatted as an "addr-spec" as defined in Section 3.4.1
+//! of [`RFC5322`], including the rare-but-legal forms found in enterprise directories: quoted
+//! local parts (`"john doe"@example.com`) and comments or folding white space (CFWS) around the
+//! parts of the address (`john.doe(finance)@example.com`). Non-ASCII characters are accepted
+//! in atoms, as allowed by [`RFC6532`].
+//!
+//! [`RFC5322`]: https://www.rfc-editor.org/info/rfc5322
+//! [`RFC6532`]: https://www.rfc-editor.org/info/rfc6532
+
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+use crate::Error;
+
+/// [EmailAddress] is a parsed "addr-spec" email address. Comments and folding white space are
+/// stripped during parsing, and the local part is kept unquoted whenever quoting is unnecessary,
+/// so that the [Display] form is the normalized address.
+///
+/// ```
+/// use subject_id::EmailAddress;
+/// let addr: EmailAddress = "(admin) \"john doe\"@example.com".parse().unwrap();
+/// assert_eq!(addr.local_part(), "john doe");
+/// assert_eq!(addr.domain(), "example.com");
+/// assert_eq!(addr.to_string(), "\"john doe\"@example.com");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct EmailAddress {
+    local: String,
+    domain: String,
+}
+
+impl EmailAddress {
+    /// The local part of the address, with quoting and quoted-pair escapes removed.
+    pub fn local_part(&self) -> &str {
+        &self.local
+    }
+
+    /// The domain of the address: either a domain name or a bracketed domain literal.
+    pub fn domain(&self) -> &str {
+        &self.domain
+    }
+
+    fn parse(s: &str) -> Result<Self, Error> {
+        let mut parser = Parser { s, pos: 0 };
+        parser.skip_cfws()?;
+        let quoted = parser.peek() == Some(b'"');
+        let local = if quoted {
+            parser.quoted_string()?
+        } else {
+            parser.dot_atom()?.to_owned()
+        };
+        parser.skip_cfws()?;
+        parser.expect(b'@')?;
+        parser.skip_cfws()?;
+        let domain = match parser.peek() {
+            Some(b'[') => parser.domain_literal()?.to_owned(),
+            _ => parser.dot_atom()?.to_owned(),
+        };
+        parser.skip_cfws()?;
+        if parser.peek().is_some() || (local.is_empty() && !quoted) {
+            return Err(Error::InvalidEmail);
+        }
+        Ok(Self { local, domain })
+    }
+}
+
+impl FromStr for EmailAddress {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl Display for EmailAddress {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if is_dot_atom(&self.local) {
+            return write!(f, "{}@{}", self.local, self.domain);
+        }
+        f.write_str("\"")?;
+        for c in self.local.chars() {
+            if c == '"' || c == '\\' {
+                f.write_str("\\")?;
+            }
+            write!(f, "{c}")?;
+        }
+        write!(f, "\"@{}", self.domain)
+    }
+}
+
+/// Characters allowed in an atom besides ASCII alphanumerics, see "atext" in RFC 5322.
+const ATEXT_SPECIALS: &[u8] = b"!#$%&'*+-/=?^_`{|}~";
+
+fn is_atext(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || ATEXT_SPECIALS.contains(&b) || b >= 0x80
+}
+
+/// Report whether the byte is an ASCII control character other than horizontal tab.
+fn is_control(b: u8) -> bool {
+    (b < 0x20 && b != b'\t') || b == 0x7f
+}
+
+fn is_dot_atom(s: &str) -> bool {
+    !s.is_empty()
+        && s.split('.')
+            .all(|atom| !atom.is_empty() && atom.bytes().all(is_atext))
+}
+
+/// Cursor over the bytes of an address. Multi-byte UTF-8 sequences only contain bytes >= 0x80,
+/// so slicing at ASCII delimiters always yields valid strings.
+struct Parser<'a> {
+    s: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<u8> {
+        self.s.as_bytes().get(self.pos).copied()
+    }
+
+    fn next(&mut self) -> Result<u8, Error> {
+        let b = self.peek().ok_or(Error::InvalidEmail)?;
+        self.pos += 1;
+        Ok(b)
+    }
+
+    fn expect(&mut self, want: u8) -> Result<(), Error> {
+        match self.next()? {
+            b if b == want => Ok(()),
+            _ => Err(Error::InvalidEmail),
+        }
+    }
+
+    /// Report whether the cursor is at a line fold: CRLF followed by white space. CR and LF are
+    /// only allowed in folding white space, never on their own.
+    fn at_fold(&self) -> bool {
+        let rest = &self.s.as_bytes()[self.pos..];
+        rest.starts_with(b"\r\n") && matches!(rest.get(2), Some(b' ' | b'\t'))
+    }
+
+    /// Skip any sequence of folding white space and (possibly nested) comments.
+    fn skip_cfws(&mut self) -> Result<(), Error> {
+        loop {
+            match self.peek() {
+                Some(b' ' | b'\t') => self.pos += 1,
+                Some(b'\r') if self.at_fold() => self.pos += 2,
+                Some(b'(') => self.comment()?,
+                _ => return Ok(()),
+            }
+        }
+    }
+
+    fn comment(&mut self) -> Result<(), Error> {
+        self.expect(b'(')?;
+        let mut depth = 1;
+        while depth > 0 {
+            if self.at_fold() {
+                self.pos += 2;
+                continue;
+            }
+            match self.next()? {
+                b'\\' => {
+                    self.next()?;
+                }
+                b'(' => depth += 1,
+                b')' => depth -= 1,
+                b if is_control(b) => return Err(Error::InvalidEmail),
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn dot_atom(&mut self) -> Result<&'a str, Error> {
+        let start = self.pos;
+        while matches!(self.peek(), Some(b) if is_atext(b) || b == b'.') {
+            self.pos += 1;
+        }
+        let atom = &self.s[start..self.pos];
+        is_dot_atom(atom).then_some(atom).ok_or(Error::InvalidEmail)
+    }
+
+    /// Parse a quoted string, which may be empty. Line folds are unfolded, and other control
+    /// characters, including CR and LF, are rejected, both as text and in quoted pairs.
+    fn quoted_string(&mut self) -> Result<String, Error> {
+        self.expect(b'"')?;
+        let mut content = Vec::new();
+        loop {
+            if self.at_fold() {
+                self.pos += 2;
+                continue;
+            }
+            match self.next()? {
+                b'"' => break,
+                b'\\' => match self.next()? {
+                    b if is_control(b) => return Err(Error::InvalidEmail),
+                    b => content.push(b),
+                },
+                b if is_control(b) => return Err(Error::InvalidEmail),
+                b => content.push(b),
+            }
+        }
+        String::from_utf8(content).map_err(|_| Error::InvalidEmail)
+    }
+
+    /// Parse a non-empty domain literal made of "dtext": printable ASCII characters other than
+    /// '[', ']' and '\\'. White space is not allowed inside the brackets.
+    fn domain_literal(&mut self) -> Result<&'a str, Error> {
+        let start = self.pos;
+        self.expect(b'[')?;
+        loop {
+            match self.next()? {
+                b']' if self.pos - start > 2 => return Ok(&self.s[start..self.pos]),
+                33..=90 | 94..=126 => {}
+                _ => return Err(Error::InvalidEmail),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_email() {
+        let cases = [
+            (
+                "user@example.com",
+                "user",
+                "example.com",
+                "user@example.com",
+            ),
+            (
+                "\"john doe\"@example.com",
+                "john doe",
+                "example.com",
+                "\"john doe\"@example.com",
+            ),
+            (
+                "\"john.doe\"@example.com",
+                "john.doe",
+                "example.com",
+                "john.doe@example.com",
+            ),
+            (
+                r#""a\"b\\c"@example.com"#,
+                r#"a"b\c"#,
+                "example.com",
+                r#""a\"b\\c"@example.com"#,
+            ),
+            (
+                "(finance (emea)) john.doe(jd) @ (mail) example.com (hq)",
+                "john.doe",
+                "example.com",
+                "john.doe@example.com",
+            ),
+            ("\"\"@example.com", "", "example.com", "\"\"@example.com"),
+            (
+                "user@[192.0.2.1]",
+                "user",
+                "[192.0.2.1]",
+                "user@[192.0.2.1]",
+            ),
+            (
+                "\"john\r\n doe\"@example.com",
+                "john doe",
+                "example.com",
+                "\"john doe\"@example.com",
+            ),
+            (
+                "john.doe\r\n\t@\r\n example.com",
+                "john.doe",
+                "example.com",
+                "john.doe@example.com",
+            ),
+            (
+                "jürgen@exämple.de",
+                "jürgen",
+                "exämple.de",
+                "jürgen@exämple.de",
+            ),
+        ];
+        for (input, local, domain, display) in cases {
+            let addr: EmailAddress = input.parse().unwrap();
+            assert_eq!(addr.local_part(), local, "local part of {input}");
+            assert_eq!(addr.domain(), domain, "domain of {input}");
+            assert_eq!(addr.to_string(), display, "display of {input}");
+        }
+    }
+
+    #[test]
+    fn test_parse_email_invalid() {
+        let cases = [
+            "",
+            "user",
+            "user@",
+            "@example.com",
+            "john doe@example.com",
+            "john..doe@example.com",
+            ".john@example.com",
+            "\"unterminated@example.com",
+            "user(unterminated@example.com",
+            "user@example.com extra",
+            "user@[a[b]",
+            "\"a\0b\"@example.com",
+            "\"a\x07\"@example.com",
+            "\"a\\\x01\"@example.com",
+            "\"\x7f\"@example.com",
+            "\"a\nb\"@example.com",
+            "\"a\rb\"@example.com",
+            "\"a\r\nb\"@example.com",
+            "a@[]",
+            "a@[\x01]",
+            "a@[ 192.0.2.1]",
+            "a@[192.0.2.1\x7f]",
+            "a@[\\]",
+            "a@b\n.c",
+            "a@b.c\n",
+            "a@b.c\r",
+            "a\r\n@b.c",
+            "a(x\ny)@b.c",
+        ];
+        for input in cases {
+            assert!(input.parse::<EmailAddress>().is_err(), "{input} is invalid");
+        }
+    }
+}
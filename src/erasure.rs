@@ -0,0 +1,134 @@
+//! `erasure` module standardizes how records tied to a subject are found across heterogeneous
+//! stores when handling a deletion (e.g. GDPR erasure) request. An [ErasureMatcher] is built from
+//! the subject of the request and matches every record whose subject identifier shares at least
+//! one canonical identifier with it, so a record filed under any alias of the subject is found.
+
+use crate::{Atomic, CanonicalPolicy, SubjectId};
+
+/// [ErasureStore] is implemented by the stores to be searched for records of a subject. The store
+/// visits each of its records along with the subject identifier the record is tied to.
+pub trait ErasureStore {
+    /// Record held by the store, e.g. a row identifier.
+    type Record;
+    /// Error reported by the store while visiting its records.
+    type Error;
+
+    /// Call `visit` for every record of the store, with the subject identifier of the record.
+    fn for_each_record(
+        &self,
+        visit: &mut dyn FnMut(&SubjectId, &Self::Record),
+    ) -> Result<(), Self::Error>;
+}
+
+/// [ErasureMatcher] matches subject identifiers against the subject of a deletion request. The
+/// identifiers of the request are canonicalized once, when the matcher is built.
+///
+/// ```
+/// use subject_id::erasure::ErasureMatcher;
+/// use subject_id::{Aliases, Atomic, CanonicalPolicy, SubjectId};
+/// let request = SubjectId::Aliases(Aliases::from(vec![
+///     Atomic::Email { email: "user@example.com".to_owned() },
+///     Atomic::Opaque { id: "1i3j4l".to_owned() },
+/// ]));
+/// let matcher = ErasureMatcher::new(&request, CanonicalPolicy::default());
+/// let record = SubjectId::Atomic(Atomic::Email { email: "user@EXAMPLE.com".to_owned() });
+/// assert!(matcher.matches(&record));
+/// ```
+#[derive(Clone, Debug)]
+pub struct ErasureMatcher {
+    identifiers: Vec<Atomic>,
+    policy: CanonicalPolicy,
+}
+
+impl ErasureMatcher {
+    /// Build a matcher for the subject of a deletion request, canonicalizing with `policy`.
+    pub fn new(request: &SubjectId, policy: CanonicalPolicy) -> Self {
+        let identifiers = canonical_identifiers(request, &policy);
+        Self {
+            identifiers,
+            policy,
+        }
+    }
+
+    /// Report whether the subject identifier shares a canonical identifier with the request.
+    pub fn matches(&self, sub_id: &SubjectId) -> bool {
+        canonical_identifiers(sub_id, &self.policy)
+            .iter()
+            .any(|id| self.identifiers.contains(id))
+    }
+
+    /// Visit every record of the store that matches the request, returning the number of
+    /// matching records.
+    pub fn for_each_match<S: ErasureStore>(
+        &self,
+        store: &S,
+        mut visit: impl FnMut(&S::Record),
+    ) -> Result<usize, S::Error> {
+        let mut count = 0;
+        store.for_each_record(&mut |sub_id, record| {
+            if self.matches(sub_id) {
+                count += 1;
+                visit(record);
+            }
+        })?;
+        Ok(count)
+    }
+}
+
+fn canonical_identifiers(sub_id: &SubjectId, policy: &CanonicalPolicy) -> Vec<Atomic> {
+    match sub_id.canonicalize(policy) {
+        SubjectId::Atomic(id) => vec![id],
+        SubjectId::Aliases(aliases) => aliases.identifiers,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Aliases;
+    use std::convert::Infallible;
+
+    struct Table(Vec<(u32, SubjectId)>);
+
+    impl ErasureStore for Table {
+        type Record = u32;
+        type Error = Infallible;
+
+        fn for_each_record(
+            &self,
+            visit: &mut dyn FnMut(&SubjectId, &u32),
+        ) -> Result<(), Infallible> {
+            self.0.iter().for_each(|(row, sub_id)| visit(sub_id, row));
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_for_each_match() {
+        let email = Atomic::Email {
+            email: "User@Example.com".to_owned(),
+        };
+        let phone = Atomic::PhoneNumber {
+            phone_number: "+12065550100".parse().unwrap(),
+        };
+        let other = Atomic::Opaque {
+            id: "1i3j4l".to_owned(),
+        };
+        let table = Table(vec![
+            (1, SubjectId::Atomic(phone.clone())),
+            (2, SubjectId::Atomic(other.clone())),
+            (3, SubjectId::Aliases(Aliases::from(vec![other, email]))),
+        ]);
+        let request = SubjectId::Aliases(Aliases::from(vec![
+            Atomic::Email {
+                email: "User@example.com".to_owned(),
+            },
+            phone,
+        ]));
+        let matcher = ErasureMatcher::new(&request, CanonicalPolicy::default());
+        let mut rows = Vec::new();
+        let count = matcher.for_each_match(&table, |row| rows.push(*row));
+        assert_eq!(count, Ok(2));
+        assert_eq!(rows, vec![1, 3]);
+    }
+}
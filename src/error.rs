@@ -5,4 +5,73 @@ use thiserror::Error;
 pub enum Error {
     #[error("invalid E.164 formatted phone number")]
     InvalidPhoneNumber,
+    #[error("invalid RFC 5322 formatted email address")]
+    InvalidEmail,
+    #[error("invalid {format} subject identifier: {reason}")]
+    InvalidSubject {
+        format: &'static str,
+        reason: String,
+    },
+    #[error("no subject identifiers remain")]
+    NoIdentifiers,
+    #[error("complex subject has no components")]
+    EmptyComplexSubject,
+    #[error("session component without user component")]
+    SessionWithoutUser,
+    #[error("missing required attribute {0:?}")]
+    MissingAttribute(&'static str),
+    #[error("invalid Cognito user pool ID")]
+    InvalidUserPool,
+    #[error("verification event does not echo the requested state")]
+    StateMismatch,
+    #[error("unsupported DID method {0:?}")]
+    UnsupportedDidMethod(String),
+    #[error("DID resolution failed: {0}")]
+    DidResolution(String),
+    #[error("undeliverable email address: {0}")]
+    UndeliverableEmail(String),
+    #[error("invalid flattened key {0:?}")]
+    InvalidFlatKey(String),
+    #[error("environment variable {0:?} is not valid Unicode")]
+    NonUnicodeEnv(String),
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("input is not valid UTF-8: invalid byte at offset {0}")]
+    InvalidUtf8(usize),
+    #[error("input is UTF-16 encoded, but UTF-16 transcoding is not enabled")]
+    Utf16NotEnabled,
+    #[error("input is not valid UTF-16")]
+    InvalidUtf16,
+    #[error("invalid subject identifier JSON: {0}")]
+    Json(#[from] serde_json::Error),
+    #[cfg(feature = "arrow")]
+    #[error("arrow conversion failed: {0}")]
+    Arrow(#[from] arrow_schema::ArrowError),
+    #[cfg(any(feature = "did-web", feature = "webfinger"))]
+    #[error("HTTP request failed: {0}")]
+    Http(#[from] reqwest::Error),
+    #[cfg(any(feature = "did-web", feature = "webfinger"))]
+    #[error("response exceeds the size limit of {0} bytes")]
+    ResponseTooLarge(usize),
+    #[cfg(feature = "dns")]
+    #[error("DNS lookup failed: {0}")]
+    Dns(#[from] hickory_resolver::net::NetError),
+    #[cfg(feature = "json5")]
+    #[error("invalid subject identifier JSON5: {0}")]
+    Json5(#[from] json5::Error),
+    #[cfg(feature = "kafka")]
+    #[error("invalid schema registry framing")]
+    InvalidRegistryFraming,
+    #[cfg(feature = "test-util")]
+    #[error("invariant violated: {0}")]
+    InvariantViolation(String),
+    #[cfg(feature = "tokio-codec")]
+    #[error("subject identifier exceeds the size limit of {0} bytes")]
+    FrameTooLarge(usize),
+    #[cfg(feature = "tokio-codec")]
+    #[error("unexpected byte {0:#04x} where a subject identifier should start")]
+    InvalidFrame(u8),
+    #[cfg(feature = "simd-json")]
+    #[error("invalid simd-json input: {0}")]
+    SimdJson(#[from] simd_json::Error),
 }
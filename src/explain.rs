@@ -0,0 +1,222 @@
+//! `explain` module reports why two subject identifiers do or do not match. Two subject
+//! identifiers match when they share an identifier once canonicalized, i.e. when any identifier
+//! of one equals any identifier of the other, like in [`crate::erasure::ErasureMatcher`].
+//!
+//! The explanation lists the canonicalization steps applied to every identifier and the outcome
+//! of every comparison, which helps debugging events that were expected to correlate.
+
+use crate::{Atomic, CanonicalPolicy, NormalizationPipeline, SubjectId};
+use std::fmt::{Display, Formatter};
+
+/// [MatchExplanation] is the result of [`SubjectId::explain_match`].
+///
+/// ```
+/// use subject_id::{Atomic, MatchOutcome, SubjectId};
+/// let a = SubjectId::Atomic(Atomic::Email { email: "user@EXAMPLE.com".to_owned() });
+/// let b = SubjectId::Atomic(Atomic::Email { email: "user@example.com".to_owned() });
+/// let explanation = a.explain_match(&b);
+/// assert!(explanation.is_match());
+/// assert_eq!(explanation.left[0].applied, vec!["email"]);
+/// assert_eq!(explanation.comparisons[0].outcome, MatchOutcome::Match { exact: false });
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MatchExplanation {
+    /// Identifiers of the subject identifier [`SubjectId::explain_match`] was called on.
+    pub left: Vec<IdentifierTrace>,
+    /// Identifiers of the other subject identifier.
+    pub right: Vec<IdentifierTrace>,
+    /// Every pair of identifiers compared, left major.
+    pub comparisons: Vec<Comparison>,
+}
+
+/// [IdentifierTrace] records the canonicalization of a single identifier.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IdentifierTrace {
+    /// The identifier as given.
+    pub original: Atomic,
+    /// The identifier once canonicalized.
+    pub canonical: Atomic,
+    /// Names of the normalization steps that changed the identifier, in order.
+    pub applied: Vec<String>,
+}
+
+/// [Comparison] is the outcome of comparing the identifiers at the given indices.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Comparison {
+    /// Index into [`MatchExplanation::left`].
+    pub left: usize,
+    /// Index into [`MatchExplanation::right`].
+    pub right: usize,
+    /// Outcome of the comparison.
+    pub outcome: MatchOutcome,
+}
+
+/// [MatchOutcome] describes why two identifiers match or not.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MatchOutcome {
+    /// The canonical identifiers are equal; `exact` tells whether they were already equal before
+    /// canonicalization.
+    Match {
+        /// Whether the identifiers as given are equal.
+        exact: bool,
+    },
+    /// The identifiers have different formats.
+    FormatMismatch,
+    /// The canonical identifiers differ in the given member.
+    MemberMismatch(&'static str),
+}
+
+impl MatchExplanation {
+    /// Report whether any comparison matched.
+    pub fn is_match(&self) -> bool {
+        self.comparisons
+            .iter()
+            .any(|c| matches!(c.outcome, MatchOutcome::Match { .. }))
+    }
+}
+
+/// Renders one line per comparison, naming formats, members and steps but no member values.
+impl Display for MatchExplanation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let verdict = if self.is_match() { "match" } else { "no match" };
+        writeln!(f, "{verdict}")?;
+        for c in &self.comparisons {
+            let (left, right) = (&self.left[c.left], &self.right[c.right]);
+            write!(
+                f,
+                "left[{}] {} vs right[{}] {}: ",
+                c.left,
+                left.original.format(),
+                c.right,
+                right.original.format()
+            )?;
+            match c.outcome {
+                MatchOutcome::Match { exact: true } => write!(f, "equal")?,
+                MatchOutcome::Match { exact: false } => write!(
+                    f,
+                    "equal after [{}] vs [{}]",
+                    left.applied.join(", "),
+                    right.applied.join(", ")
+                )?,
+                MatchOutcome::FormatMismatch => write!(f, "formats differ")?,
+                MatchOutcome::MemberMismatch(name) => write!(f, "{name:?} differs")?,
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+impl SubjectId {
+    /// Explain whether the [SubjectId] matches the other one under the default
+    /// [CanonicalPolicy], see [MatchExplanation].
+    pub fn explain_match(&self, other: &SubjectId) -> MatchExplanation {
+        self.explain_match_with(other, &CanonicalPolicy::default())
+    }
+
+    /// Explain whether the [SubjectId] matches the other one under the given policy.
+    pub fn explain_match_with(
+        &self,
+        other: &SubjectId,
+        policy: &CanonicalPolicy,
+    ) -> MatchExplanation {
+        let pipeline = NormalizationPipeline::from(policy);
+        let (left, right) = (traces(self, &pipeline), traces(other, &pipeline));
+        let mut comparisons = Vec::new();
+        for (i, l) in left.iter().enumerate() {
+            for (j, r) in right.iter().enumerate() {
+                comparisons.push(Comparison {
+                    left: i,
+                    right: j,
+                    outcome: compare(l, r),
+                });
+            }
+        }
+        MatchExplanation {
+            left,
+            right,
+            comparisons,
+        }
+    }
+}
+
+fn traces(sub_id: &SubjectId, pipeline: &NormalizationPipeline) -> Vec<IdentifierTrace> {
+    let identifiers = match sub_id {
+        SubjectId::Atomic(id) => std::slice::from_ref(id),
+        SubjectId::Aliases(aliases) => &aliases.identifiers[..],
+    };
+    identifiers
+        .iter()
+        .map(|id| {
+            let (canonical, applied) = pipeline.normalize_traced(id.clone());
+            IdentifierTrace {
+                original: id.clone(),
+                canonical,
+                applied,
+            }
+        })
+        .collect()
+}
+
+fn compare(left: &IdentifierTrace, right: &IdentifierTrace) -> MatchOutcome {
+    if left.canonical.format() != right.canonical.format() {
+        return MatchOutcome::FormatMismatch;
+    }
+    let members = left.canonical.members().into_iter();
+    match members
+        .zip(right.canonical.members())
+        .find(|((_, a), (_, b))| a != b)
+    {
+        Some(((name, _), _)) => MatchOutcome::MemberMismatch(name),
+        None => MatchOutcome::Match {
+            exact: left.original == right.original,
+        },
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Aliases;
+
+    #[test]
+    fn test_explain_match() {
+        let left = SubjectId::Aliases(Aliases::from(vec![
+            Atomic::IssuerSubject {
+                issuer: "https://issuer.example.com".to_owned(),
+                subject: "abc".to_owned(),
+            },
+            Atomic::Opaque {
+                id: "1i3j4l".to_owned(),
+            },
+        ]));
+        let right = SubjectId::Atomic(Atomic::IssuerSubject {
+            issuer: "https://issuer.example.com".to_owned(),
+            subject: "abd".to_owned(),
+        });
+        let explanation = left.explain_match(&right);
+        assert!(!explanation.is_match());
+        let outcomes: Vec<_> = explanation
+            .comparisons
+            .iter()
+            .map(|c| c.outcome.clone())
+            .collect();
+        assert_eq!(
+            outcomes,
+            [
+                MatchOutcome::MemberMismatch("subject"),
+                MatchOutcome::FormatMismatch
+            ]
+        );
+        let want = "no match\n\
+            left[0] iss_sub vs right[0] iss_sub: \"subject\" differs\n\
+            left[1] opaque vs right[0] iss_sub: formats differ\n";
+        assert_eq!(explanation.to_string(), want);
+
+        let explanation = right.explain_match(&right);
+        assert_eq!(
+            explanation.comparisons[0].outcome,
+            MatchOutcome::Match { exact: true }
+        );
+    }
+}
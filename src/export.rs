@@ -0,0 +1,208 @@
+//! `export` module writes flat exports of subject identifiers, such as the lists of affected
+//! subjects that compliance teams request after incidents.
+
+use crate::{Error, PseudonymKey, SubjectId};
+use std::borrow::Cow;
+use std::io::Write;
+
+/// [Column] selects a column of a CSV export, see [`to_csv`].
+#[derive(Clone, Debug)]
+pub enum Column {
+    /// The subject identifier format, see [`SubjectId::format`].
+    Format,
+    /// The value that identifies the subject, see [`Atomic::value`]. The values of the
+    /// identifiers of an "aliases" subject identifier are joined with `;`, after escaping `\`
+    /// and `;` in each value with a backslash.
+    ///
+    /// [`Atomic::value`]: crate::Atomic::value
+    Value,
+    /// The domain of the subject, see [`SubjectId::domain`]. Empty if there is none.
+    Domain,
+    /// The fingerprint of the subject under the given key, see [`SubjectId::fingerprint`].
+    Fingerprint(PseudonymKey),
+}
+
+impl Column {
+    /// Name of the column in the header row.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Column::Format => "format",
+            Column::Value => "value",
+            Column::Domain => "domain",
+            Column::Fingerprint(_) => "fingerprint",
+        }
+    }
+
+    fn value(&self, sub_id: &SubjectId) -> String {
+        match self {
+            Column::Format => sub_id.format().to_owned(),
+            Column::Value => match sub_id {
+                SubjectId::Atomic(id) => id.value().to_owned(),
+                SubjectId::Aliases(aliases) => {
+                    let values: Vec<_> = aliases
+                        .identifiers
+                        .iter()
+                        .map(|id| id.value().replace('\\', "\\\\").replace(';', "\\;"))
+                        .collect();
+                    values.join(";")
+                }
+            },
+            Column::Domain => sub_id.domain().unwrap_or_default().to_owned(),
+            Column::Fingerprint(key) => sub_id.fingerprint(key),
+        }
+    }
+}
+
+/// [CsvOptions] tunes how [`to_csv_with`] writes fields.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CsvOptions {
+    /// Prefix fields starting with `=`, `+`, `-`, `@`, a tab or a carriage return with `'`, so
+    /// that spreadsheet applications do not evaluate them as formulas. Enabled by default; note
+    /// that phone numbers start with `+` and are therefore prefixed too.
+    pub escape_formulas: bool,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        Self {
+            escape_formulas: true,
+        }
+    }
+}
+
+/// Write the subject identifiers as CSV ([`RFC4180`]) with the given columns and the default
+/// options, see [`to_csv_with`].
+///
+/// [`RFC4180`]: https://www.rfc-editor.org/info/rfc4180
+///
+/// ```
+/// use subject_id::export::{to_csv, Column};
+/// use subject_id::{Atomic, SubjectId};
+/// let sub_ids = [SubjectId::Atomic(Atomic::Email { email: "user@example.com".to_owned() })];
+/// let mut out = Vec::new();
+/// to_csv(&mut out, &sub_ids, &[Column::Format, Column::Value]).unwrap();
+/// assert_eq!(out, b"format,value\r\nemail,user@example.com\r\n");
+/// ```
+pub fn to_csv<W: Write>(writer: W, sub_ids: &[SubjectId], columns: &[Column]) -> Result<(), Error> {
+    to_csv_with(writer, sub_ids, columns, &CsvOptions::default())
+}
+
+/// Write the subject identifiers as CSV ([`RFC4180`]) with the given columns and options: a
+/// header row followed by one row per subject identifier. Fields containing commas, double quotes
+/// or line breaks are quoted, and lines end with CRLF.
+///
+/// [`RFC4180`]: https://www.rfc-editor.org/info/rfc4180
+///
+/// ```
+/// use subject_id::export::{to_csv_with, Column, CsvOptions};
+/// use subject_id::{Atomic, SubjectId};
+/// let sub_ids = [SubjectId::Atomic(Atomic::PhoneNumber { phone_number: "+12065550100".parse().unwrap() })];
+/// let mut out = Vec::new();
+/// to_csv_with(&mut out, &sub_ids, &[Column::Value], &CsvOptions::default()).unwrap();
+/// assert_eq!(out, b"value\r\n'+12065550100\r\n");
+/// ```
+pub fn to_csv_with<W: Write>(
+    mut writer: W,
+    sub_ids: &[SubjectId],
+    columns: &[Column],
+    options: &CsvOptions,
+) -> Result<(), Error> {
+    let header: Vec<_> = columns
+        .iter()
+        .map(|column| column.name().to_owned())
+        .collect();
+    write_record(&mut writer, &header, options)?;
+    for sub_id in sub_ids {
+        let record: Vec<_> = columns.iter().map(|column| column.value(sub_id)).collect();
+        write_record(&mut writer, &record, options)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+fn write_record<W: Write>(
+    writer: &mut W,
+    fields: &[String],
+    options: &CsvOptions,
+) -> Result<(), Error> {
+    for (i, field) in fields.iter().enumerate() {
+        if i > 0 {
+            writer.write_all(b",")?;
+        }
+        let formula = field.starts_with(['=', '+', '-', '@', '\t', '\r']);
+        let field = if options.escape_formulas && formula {
+            Cow::Owned(format!("'{field}"))
+        } else {
+            Cow::Borrowed(field)
+        };
+        if field.contains([',', '"', '\r', '\n']) {
+            write!(writer, "\"{}\"", field.replace('"', "\"\""))?;
+        } else {
+            writer.write_all(field.as_bytes())?;
+        }
+    }
+    writer.write_all(b"\r\n")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{Aliases, Atomic};
+
+    #[test]
+    fn test_to_csv() {
+        let sub_ids = [
+            SubjectId::Atomic(Atomic::IssuerSubject {
+                issuer: "https://issuer.example.com/".to_owned(),
+                subject: "a,\"b\"".to_owned(),
+            }),
+            SubjectId::Aliases(Aliases::from(vec![
+                Atomic::Opaque {
+                    id: "1i3j4l".to_owned(),
+                },
+                Atomic::Email {
+                    email: "user@example.com".to_owned(),
+                },
+            ])),
+        ];
+        let mut out = Vec::new();
+        let columns = [Column::Format, Column::Value, Column::Domain];
+        to_csv(&mut out, &sub_ids, &columns).unwrap();
+        let want = "format,value,domain\r\n\
+            iss_sub,\"a,\"\"b\"\"\",issuer.example.com\r\n\
+            aliases,1i3j4l;user@example.com,example.com\r\n";
+        assert_eq!(String::from_utf8(out).unwrap(), want);
+    }
+
+    #[test]
+    fn test_to_csv_escaping() {
+        let sub_ids = [
+            SubjectId::Atomic(Atomic::Opaque {
+                id: "=HYPERLINK(\"http://evil.example\")".to_owned(),
+            }),
+            SubjectId::Aliases(Aliases::from(vec![
+                Atomic::Opaque {
+                    id: "a;b\\".to_owned(),
+                },
+                Atomic::PhoneNumber {
+                    phone_number: "+12065550100".parse().unwrap(),
+                },
+            ])),
+        ];
+        let mut out = Vec::new();
+        to_csv(&mut out, &sub_ids, &[Column::Value]).unwrap();
+        let want = "value\r\n\
+            \"'=HYPERLINK(\"\"http://evil.example\"\")\"\r\n\
+            a\\;b\\\\;+12065550100\r\n";
+        assert_eq!(String::from_utf8(out).unwrap(), want);
+
+        let mut out = Vec::new();
+        let options = CsvOptions {
+            escape_formulas: false,
+        };
+        to_csv_with(&mut out, &sub_ids[..1], &[Column::Value], &options).unwrap();
+        let want = "value\r\n\"=HYPERLINK(\"\"http://evil.example\"\")\"\r\n";
+        assert_eq!(String::from_utf8(out).unwrap(), want);
+    }
+}
@@ -0,0 +1,268 @@
+//! `flat` module converts subject identifiers to flat key-value pairs with dotted keys, for sinks
+//! such as syslog structured data or legacy SIEM field mappings that cannot accept nested JSON.
+//! Object members are keyed by their member name and array elements by their index, e.g.
+//! `identifiers.0.email` for the "email" member of the first identifier of an "aliases"
+//! subject identifier.
+//!
+//! The inverse conversion builds subject identifiers from such pairs, e.g. from environment
+//! variables, so that deployment-time configuration of watched subjects does not require
+//! embedding JSON in environment values.
+
+use crate::{Error, SubjectId};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::ffi::OsString;
+
+impl SubjectId {
+    /// Flatten the [SubjectId] into key-value pairs with dotted keys.
+    ///
+    /// ```
+    /// use subject_id::{Aliases, Atomic, SubjectId};
+    /// let sub_id = SubjectId::Aliases(Aliases::from(vec![
+    ///     Atomic::Email { email: "user@example.com".to_owned() },
+    /// ]));
+    /// let flat = sub_id.to_flat_map();
+    /// assert_eq!(flat["format"], "aliases");
+    /// assert_eq!(flat["identifiers.0.format"], "email");
+    /// assert_eq!(flat["identifiers.0.email"], "user@example.com");
+    /// ```
+    pub fn to_flat_map(&self) -> BTreeMap<String, String> {
+        let value = serde_json::to_value(self).expect("subject identifiers always serialize");
+        let mut flat = BTreeMap::new();
+        flatten(&mut flat, String::new(), value);
+        flat
+    }
+
+    /// Build a [SubjectId] from key-value pairs with dotted keys, the inverse of
+    /// [`SubjectId::to_flat_map`].
+    ///
+    /// ```
+    /// use subject_id::SubjectId;
+    /// let sub_id = SubjectId::from_flat_map([
+    ///     ("format", "aliases"),
+    ///     ("identifiers.0.format", "email"),
+    ///     ("identifiers.0.email", "user@example.com"),
+    /// ])
+    /// .unwrap();
+    /// assert_eq!(sub_id.format(), SubjectId::FORMAT_ALIASES);
+    /// ```
+    pub fn from_flat_map<K, V>(pairs: impl IntoIterator<Item = (K, V)>) -> Result<Self, Error>
+    where
+        K: AsRef<str>,
+        V: Into<String>,
+    {
+        let mut root = Map::new();
+        for (key, value) in pairs {
+            let key = key.as_ref();
+            let mut names = key.split('.').peekable();
+            let mut object = &mut root;
+            while let Some(name) = names.next() {
+                if name.is_empty() {
+                    return Err(Error::InvalidFlatKey(key.to_owned()));
+                }
+                if names.peek().is_none() {
+                    object.insert(name.to_owned(), Value::String(value.into()));
+                    break;
+                }
+                let child = object
+                    .entry(name)
+                    .or_insert_with(|| Value::Object(Map::new()));
+                object = child
+                    .as_object_mut()
+                    .ok_or_else(|| Error::InvalidFlatKey(key.to_owned()))?;
+            }
+        }
+        Ok(serde_json::from_value(unflatten(Value::Object(root))?)?)
+    }
+
+    /// Build a [SubjectId] from the environment variables whose name starts with `prefix`
+    /// followed by `_`. The rest of the name is lowercased and maps to a dotted key, where
+    /// `IDENTIFIERS_<n>_` selects the identifier at index `n` of an "aliases" subject identifier:
+    ///
+    /// ```text
+    /// SUBJECT_FORMAT=aliases
+    /// SUBJECT_IDENTIFIERS_0_FORMAT=phone_number
+    /// SUBJECT_IDENTIFIERS_0_PHONE_NUMBER=+12065550100
+    /// ```
+    ///
+    /// Other environment variables are ignored, even if they are not valid Unicode; a matching
+    /// variable whose name or value is not valid Unicode is an error.
+    pub fn from_env(prefix: &str) -> Result<Self, Error> {
+        Self::from_vars(prefix, std::env::vars_os())
+    }
+
+    fn from_vars(
+        prefix: &str,
+        vars: impl IntoIterator<Item = (OsString, OsString)>,
+    ) -> Result<Self, Error> {
+        let prefix = format!("{prefix}_");
+        let mut pairs = Vec::new();
+        for (name, value) in vars {
+            if !name.as_encoded_bytes().starts_with(prefix.as_bytes()) {
+                continue;
+            }
+            let (Some(name), Some(value)) = (name.to_str(), value.to_str()) else {
+                return Err(Error::NonUnicodeEnv(name.to_string_lossy().into_owned()));
+            };
+            let rest = name[prefix.len()..].to_lowercase();
+            let key = match rest.strip_prefix("identifiers_") {
+                Some(rest) => match rest.split_once('_') {
+                    Some((index, member)) => format!("identifiers.{index}.{member}"),
+                    None => format!("identifiers.{rest}"),
+                },
+                None => rest,
+            };
+            pairs.push((key, value.to_owned()));
+        }
+        Self::from_flat_map(pairs)
+    }
+}
+
+/// Turn objects whose keys are all indices `0..n` into arrays, recursively.
+fn unflatten(value: Value) -> Result<Value, Error> {
+    let Value::Object(members) = value else {
+        return Ok(value);
+    };
+    let is_array = !members.is_empty() && members.keys().all(|k| k.parse::<usize>().is_ok());
+    if !is_array {
+        let members = members
+            .into_iter()
+            .map(|(name, value)| Ok((name, unflatten(value)?)))
+            .collect::<Result<_, Error>>()?;
+        return Ok(Value::Object(members));
+    }
+    let mut elements: Vec<(usize, Value)> = members
+        .into_iter()
+        .map(|(index, value)| Ok((index.parse().unwrap_or_default(), unflatten(value)?)))
+        .collect::<Result<_, Error>>()?;
+    elements.sort_by_key(|(index, _)| *index);
+    if elements
+        .iter()
+        .enumerate()
+        .any(|(i, (index, _))| i != *index)
+    {
+        return Err(Error::InvalidFlatKey("non-contiguous indices".to_owned()));
+    }
+    Ok(Value::Array(elements.into_iter().map(|(_, v)| v).collect()))
+}
+
+fn flatten(flat: &mut BTreeMap<String, String>, key: String, value: Value) {
+    let child = |name: &str| match key.is_empty() {
+        true => name.to_owned(),
+        false => format!("{key}.{name}"),
+    };
+    match value {
+        Value::Object(members) => {
+            for (name, value) in members {
+                flatten(flat, child(&name), value);
+            }
+        }
+        Value::Array(elements) => {
+            for (i, value) in elements.into_iter().enumerate() {
+                flatten(flat, child(&i.to_string()), value);
+            }
+        }
+        Value::String(value) => {
+            flat.insert(key, value);
+        }
+        value => {
+            flat.insert(key, value.to_string());
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{Aliases, Atomic};
+
+    #[test]
+    fn test_to_flat_map() {
+        let sub_id = SubjectId::Aliases(Aliases::from(vec![
+            Atomic::IssuerSubject {
+                issuer: "https://issuer.example.com/".to_owned(),
+                subject: "145234573".to_owned(),
+            },
+            Atomic::PhoneNumber {
+                phone_number: "+12065550100".parse().unwrap(),
+            },
+        ]));
+        let want = BTreeMap::from(
+            [
+                ("format", "aliases"),
+                ("identifiers.0.format", "iss_sub"),
+                ("identifiers.0.issuer", "https://issuer.example.com/"),
+                ("identifiers.0.subject", "145234573"),
+                ("identifiers.1.format", "phone_number"),
+                ("identifiers.1.phone_number", "+12065550100"),
+            ]
+            .map(|(k, v)| (k.to_owned(), v.to_owned())),
+        );
+        assert_eq!(sub_id.to_flat_map(), want);
+    }
+
+    #[test]
+    fn test_from_flat_map() {
+        let sub_id = SubjectId::Aliases(crate::Aliases::from(vec![
+            Atomic::Did {
+                url: "did:example:123456".to_owned(),
+            },
+            Atomic::Opaque {
+                id: "1i3j4l".to_owned(),
+            },
+        ]));
+        assert_eq!(
+            SubjectId::from_flat_map(sub_id.to_flat_map()).unwrap(),
+            sub_id
+        );
+        let got =
+            SubjectId::from_flat_map([("format", "aliases"), ("identifiers.1.format", "did")]);
+        assert!(matches!(got, Err(Error::InvalidFlatKey(..))));
+        let got = SubjectId::from_flat_map([("format", "opaque"), ("format.x", "y")]);
+        assert!(matches!(got, Err(Error::InvalidFlatKey(..))));
+    }
+
+    #[test]
+    fn test_from_vars() {
+        let vars = [
+            ("SUBJECT_FORMAT", "aliases"),
+            ("SUBJECT_IDENTIFIERS_0_FORMAT", "phone_number"),
+            ("SUBJECT_IDENTIFIERS_0_PHONE_NUMBER", "+12065550100"),
+            ("SUBJECT_IDENTIFIERS_1_FORMAT", "email"),
+            ("SUBJECT_IDENTIFIERS_1_EMAIL", "user@example.com"),
+            ("OTHER_FORMAT", "opaque"),
+        ]
+        .map(|(k, v)| (OsString::from(k), OsString::from(v)));
+        let want = SubjectId::Aliases(crate::Aliases::from(vec![
+            Atomic::PhoneNumber {
+                phone_number: "+12065550100".parse().unwrap(),
+            },
+            Atomic::Email {
+                email: "user@example.com".to_owned(),
+            },
+        ]));
+        assert_eq!(SubjectId::from_vars("SUBJECT", vars).unwrap(), want);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_from_vars_non_unicode() {
+        use std::os::unix::ffi::OsStringExt;
+        let invalid = || OsString::from_vec(vec![b'a', 0xff]);
+        let vars = [
+            (OsString::from("SUBJECT_FORMAT"), OsString::from("opaque")),
+            (OsString::from("SUBJECT_ID"), OsString::from("1i3j4l")),
+            (invalid(), invalid()),
+        ];
+        let want = SubjectId::Atomic(Atomic::Opaque {
+            id: "1i3j4l".to_owned(),
+        });
+        assert_eq!(SubjectId::from_vars("SUBJECT", vars).unwrap(), want);
+        let vars = [
+            (OsString::from("SUBJECT_FORMAT"), OsString::from("opaque")),
+            (OsString::from("SUBJECT_ID"), invalid()),
+        ];
+        let err = SubjectId::from_vars("SUBJECT", vars).unwrap_err();
+        assert!(matches!(err, Error::NonUnicodeEnv(name) if name == "SUBJECT_ID"));
+    }
+}
@@ -0,0 +1,74 @@
+//! `http` module holds the rules shared by the lookups that fetch documents named by subject
+//! identifiers, so that an identifier cannot point a request at internal endpoints or make it
+//! buffer an unbounded response.
+
+use crate::Error;
+
+/// Report whether the host is a DNS name of at least two labels made of letters, digits and
+/// hyphens, whose top-level label is not numeric, and which is not "localhost".
+pub(crate) fn is_dns_name(host: &str) -> bool {
+    let labels: Vec<_> = host.split('.').collect();
+    let is_label = |label: &str| {
+        (1..=63).contains(&label.len())
+            && label
+                .bytes()
+                .all(|b| b.is_ascii_alphanumeric() || b == b'-')
+            && !label.starts_with('-')
+            && !label.ends_with('-')
+    };
+    let tld = labels[labels.len() - 1];
+    host.len() <= 253
+        && labels.len() >= 2
+        && labels.iter().all(|label| is_label(label))
+        && !tld.bytes().all(|b| b.is_ascii_digit())
+        && !tld.eq_ignore_ascii_case("localhost")
+}
+
+/// Read the body of the response, failing with [`Error::ResponseTooLarge`] as soon as it exceeds
+/// `max_len` bytes.
+pub(crate) async fn read_body(
+    mut response: reqwest::Response,
+    max_len: usize,
+) -> Result<Vec<u8>, Error> {
+    if response
+        .content_length()
+        .is_some_and(|len| len > max_len as u64)
+    {
+        return Err(Error::ResponseTooLarge(max_len));
+    }
+    let mut body = Vec::new();
+    while let Some(chunk) = response.chunk().await? {
+        if body.len() + chunk.len() > max_len {
+            return Err(Error::ResponseTooLarge(max_len));
+        }
+        body.extend_from_slice(&chunk);
+    }
+    Ok(body)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_is_dns_name() {
+        for host in ["example.com", "w3c-ccg.github.io", "xn--bcher-kva.example"] {
+            assert!(is_dns_name(host), "{host}");
+        }
+        let hostile = [
+            "localhost",
+            "internal.localhost",
+            "127.0.0.1",
+            "[::1]",
+            "intranet",
+            "example.com:8080",
+            "example.com/admin",
+            "a@internal",
+            "-example.com",
+            "example..com",
+        ];
+        for host in hostile {
+            assert!(!is_dns_name(host), "{host}");
+        }
+    }
+}
@@ -0,0 +1,320 @@
+//! `index` module defines [SubjectIdIndex], an ordered index of subject identifiers over their
+//! canonical form, for receivers that look up subjects in bulk: all subjects of an issuer, all
+//! email addresses of a domain, or all identifiers of a format within a range.
+//!
+//! Every identifier is indexed under a key made of its format and its canonical member values,
+//! separated by NUL characters, so that keys sort by format, then by member values:
+//!
+//! - "email" addresses and "account" URIs by reversed domain labels, then local part, so that a
+//!   domain and its subdomains are adjacent,
+//! - "iss_sub" identifiers by issuer, then subject,
+//! - other identifiers by their only member.
+//!
+//! Within member values, NUL is escaped as U+0001 U+0001 and U+0001 as U+0001 U+0002, so that
+//! distinct identifiers never share a key and keys keep the order of the values. Prefixes and
+//! ranges given to [`SubjectIdIndex::prefix`] and [`SubjectIdIndex::range`] are compared with
+//! the escaped values.
+//!
+//! The identifiers of an "aliases" subject identifier are indexed separately, and queries report
+//! every subject identifier once.
+
+use crate::{Atomic, CanonicalPolicy, CaseFolding, SubjectId};
+use std::collections::{BTreeMap, HashSet};
+use std::ops::{Bound, RangeBounds};
+
+/// Separator of the parts of a key.
+const SEP: char = '\0';
+
+/// Escape character of member values.
+const ESC: char = '\u{1}';
+
+/// [SubjectIdIndex] maps subject identifiers to values, ordered by canonical keys.
+///
+/// ```
+/// use subject_id::{Atomic, SubjectId, SubjectIdIndex};
+/// let mut index = SubjectIdIndex::new();
+/// let email = |email: &str| SubjectId::Atomic(Atomic::Email { email: email.to_owned() });
+/// index.insert(email("alice@example.com"), 1);
+/// index.insert(email("bob@eu.example.com"), 2);
+/// index.insert(email("carol@example.org"), 3);
+/// let values: Vec<_> = index.by_email_domain("Example.COM").map(|(_, v)| *v).collect();
+/// assert_eq!(values, [1, 2]);
+/// ```
+#[derive(Clone, Debug)]
+pub struct SubjectIdIndex<V> {
+    policy: CanonicalPolicy,
+    keys: BTreeMap<String, Vec<usize>>,
+    entries: Vec<(SubjectId, V)>,
+}
+
+impl<V> SubjectIdIndex<V> {
+    /// Empty index canonicalizing with the default policy.
+    pub fn new() -> Self {
+        Self::with_policy(CanonicalPolicy::default())
+    }
+
+    /// Empty index canonicalizing with the given policy.
+    pub fn with_policy(policy: CanonicalPolicy) -> Self {
+        Self {
+            policy,
+            keys: BTreeMap::new(),
+            entries: Vec::new(),
+        }
+    }
+
+    /// Number of subject identifiers in the index.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Report whether the index is empty.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Insert the subject identifier with its value. Equivalent subject identifiers are kept as
+    /// separate entries.
+    pub fn insert(&mut self, sub_id: SubjectId, value: V) {
+        let entry = self.entries.len();
+        for key in self.keys_of(&sub_id) {
+            let entries = self.keys.entry(key).or_default();
+            if !entries.contains(&entry) {
+                entries.push(entry);
+            }
+        }
+        self.entries.push((sub_id, value));
+    }
+
+    /// Subject identifiers sharing a canonical identifier with the given one.
+    pub fn get(&self, sub_id: &SubjectId) -> impl Iterator<Item = (&SubjectId, &V)> {
+        let mut found = Vec::new();
+        for key in self.keys_of(sub_id) {
+            found.extend(self.keys.get(&key).into_iter().flatten().copied());
+        }
+        self.resolve(found)
+    }
+
+    /// Subject identifiers with an identifier of the format whose key, after the format, starts
+    /// with the prefix; see the module documentation for the layout of keys.
+    pub fn prefix(&self, format: &str, prefix: &str) -> impl Iterator<Item = (&SubjectId, &V)> {
+        let start = format!("{format}{SEP}{prefix}");
+        let found = self
+            .keys
+            .range(start.clone()..)
+            .take_while(|(key, _)| key.starts_with(&start))
+            .flat_map(|(_, entries)| entries.iter().copied())
+            .collect();
+        self.resolve(found)
+    }
+
+    /// Subject identifiers with an identifier of the format whose key, after the format, is
+    /// within the range; see the module documentation for the layout of keys.
+    ///
+    /// ```
+    /// use subject_id::{Atomic, SubjectId, SubjectIdIndex};
+    /// let mut index = SubjectIdIndex::new();
+    /// for id in ["a1", "b2", "c3"] {
+    ///     index.insert(SubjectId::Atomic(Atomic::Opaque { id: id.to_owned() }), id);
+    /// }
+    /// let values: Vec<_> = index.range("opaque", "b".."c").map(|(_, v)| *v).collect();
+    /// assert_eq!(values, ["b2"]);
+    /// ```
+    pub fn range<'a>(
+        &'a self,
+        format: &str,
+        range: impl RangeBounds<&'a str>,
+    ) -> impl Iterator<Item = (&'a SubjectId, &'a V)> {
+        let key = |bound: Bound<&&str>| match bound {
+            Bound::Included(s) => Bound::Included(format!("{format}{SEP}{s}")),
+            Bound::Excluded(s) => Bound::Excluded(format!("{format}{SEP}{s}")),
+            Bound::Unbounded => Bound::Unbounded,
+        };
+        let start = match key(range.start_bound()) {
+            Bound::Unbounded => Bound::Included(format!("{format}{SEP}")),
+            bound => bound,
+        };
+        let end = match key(range.end_bound()) {
+            Bound::Unbounded => Bound::Excluded(format!("{format}\u{1}")),
+            bound => bound,
+        };
+        let found = self
+            .keys
+            .range((start, end))
+            .flat_map(|(_, entries)| entries.iter().copied())
+            .collect();
+        self.resolve(found)
+    }
+
+    /// Subject identifiers with an "iss_sub" identifier of the issuer.
+    pub fn by_issuer(&self, issuer: &str) -> impl Iterator<Item = (&SubjectId, &V)> {
+        let issuer = escape(issuer);
+        self.prefix(SubjectId::FORMAT_ISSUER_SUBJECT, &format!("{issuer}{SEP}"))
+    }
+
+    /// Subject identifiers with an "email" identifier in the domain or its subdomains, ordered
+    /// by reversed domain labels.
+    pub fn by_email_domain(&self, domain: &str) -> impl Iterator<Item = (&SubjectId, &V)> {
+        let domain = match self.policy.case_folding {
+            CaseFolding::Preserve => domain.to_owned(),
+            _ => domain.to_lowercase(),
+        };
+        let reversed = escape(&reverse_domain(&domain));
+        let start = format!("{}{SEP}{reversed}", SubjectId::FORMAT_EMAIL);
+        let found = self
+            .keys
+            .range(start.clone()..)
+            .take_while(|(key, _)| key.starts_with(&start))
+            .filter(|(key, _)| matches!(key[start.len()..].chars().next(), Some(SEP | '.')))
+            .flat_map(|(_, entries)| entries.iter().copied())
+            .collect();
+        self.resolve(found)
+    }
+
+    /// Iterate over the subject identifiers in insertion order.
+    pub fn iter(&self) -> impl Iterator<Item = (&SubjectId, &V)> {
+        self.entries.iter().map(|(sub_id, value)| (sub_id, value))
+    }
+
+    fn keys_of(&self, sub_id: &SubjectId) -> Vec<String> {
+        match sub_id.canonicalize(&self.policy) {
+            SubjectId::Atomic(id) => vec![key(&id)],
+            SubjectId::Aliases(aliases) => aliases.identifiers.iter().map(key).collect(),
+        }
+    }
+
+    /// The entries at the given positions, without duplicates, in the order first found.
+    fn resolve(&self, mut found: Vec<usize>) -> impl Iterator<Item = (&SubjectId, &V)> {
+        let mut seen = HashSet::with_capacity(found.len());
+        found.retain(|&entry| seen.insert(entry));
+        found.into_iter().map(|entry| {
+            let (sub_id, value) = &self.entries[entry];
+            (sub_id, value)
+        })
+    }
+}
+
+impl<V> Default for SubjectIdIndex<V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<V> FromIterator<(SubjectId, V)> for SubjectIdIndex<V> {
+    fn from_iter<T: IntoIterator<Item = (SubjectId, V)>>(iter: T) -> Self {
+        let mut index = Self::new();
+        for (sub_id, value) in iter {
+            index.insert(sub_id, value);
+        }
+        index
+    }
+}
+
+/// Key of a canonical atomic identifier.
+fn key(id: &Atomic) -> String {
+    let address = |address: &str| match address.rsplit_once('@') {
+        Some((local, domain)) => {
+            format!("{}{SEP}{}", escape(&reverse_domain(domain)), escape(local))
+        }
+        None => format!("{SEP}{}", escape(address)),
+    };
+    let members = match id {
+        Atomic::Email { email } => address(email),
+        Atomic::Account { uri } => address(uri.strip_prefix("acct:").unwrap_or(uri)),
+        Atomic::IssuerSubject { issuer, subject } => {
+            format!("{}{SEP}{}", escape(issuer), escape(subject))
+        }
+        id => escape(id.value()),
+    };
+    format!("{}{SEP}{members}", id.format())
+}
+
+/// Escape the separator in a member value, see the module documentation.
+fn escape(value: &str) -> String {
+    if !value.contains([SEP, ESC]) {
+        return value.to_owned();
+    }
+    value.replace(ESC, "\u{1}\u{2}").replace(SEP, "\u{1}\u{1}")
+}
+
+/// Reverse the labels of a domain, e.g. "eu.example.com" becomes "com.example.eu".
+fn reverse_domain(domain: &str) -> String {
+    domain.rsplit('.').collect::<Vec<_>>().join(".")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Aliases;
+
+    #[test]
+    fn test_queries() {
+        let iss_sub = |issuer: &str, subject: &str| Atomic::IssuerSubject {
+            issuer: issuer.to_owned(),
+            subject: subject.to_owned(),
+        };
+        let email = |email: &str| Atomic::Email {
+            email: email.to_owned(),
+        };
+        let index: SubjectIdIndex<u32> = [
+            (SubjectId::Atomic(iss_sub("https://idp.example/", "b")), 1),
+            (
+                SubjectId::Aliases(Aliases::from(vec![
+                    iss_sub("https://idp.example/", "a"),
+                    email("a@Example.com"),
+                ])),
+                2,
+            ),
+            (
+                SubjectId::Atomic(iss_sub("https://idp.example.org/", "a")),
+                3,
+            ),
+            (SubjectId::Atomic(email("x@notexample.com")), 4),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(index.len(), 4);
+
+        let values = |it: Box<dyn Iterator<Item = (&SubjectId, &u32)> + '_>| {
+            it.map(|(_, v)| *v).collect::<Vec<_>>()
+        };
+        assert_eq!(
+            values(Box::new(index.by_issuer("https://idp.example/"))),
+            [2, 1]
+        );
+        assert_eq!(values(Box::new(index.by_email_domain("example.com"))), [2]);
+        let got = index.get(&SubjectId::Atomic(email("a@EXAMPLE.COM")));
+        assert_eq!(values(Box::new(got)), [2]);
+        let got = index.range(
+            SubjectId::FORMAT_ISSUER_SUBJECT,
+            "https://idp.example.org/".."https://idp.example/",
+        );
+        assert_eq!(values(Box::new(got)), [3]);
+        assert_eq!(values(Box::new(index.prefix("email", "com."))), [2, 4]);
+    }
+
+    #[test]
+    fn test_separator_in_values() {
+        let iss_sub = |issuer: &str, subject: &str| {
+            SubjectId::Atomic(Atomic::IssuerSubject {
+                issuer: issuer.to_owned(),
+                subject: subject.to_owned(),
+            })
+        };
+        let index: SubjectIdIndex<u32> = [
+            (iss_sub("a\0b", "c"), 1),
+            (iss_sub("a", "b\0c"), 2),
+            (iss_sub("a\u{1}", "b"), 3),
+        ]
+        .into_iter()
+        .collect();
+        let values = |it: Box<dyn Iterator<Item = (&SubjectId, &u32)> + '_>| {
+            it.map(|(_, v)| *v).collect::<Vec<_>>()
+        };
+        assert_eq!(values(Box::new(index.get(&iss_sub("a\0b", "c")))), [1]);
+        assert_eq!(values(Box::new(index.get(&iss_sub("a", "b\0c")))), [2]);
+        assert_eq!(values(Box::new(index.by_issuer("a"))), [2]);
+        assert_eq!(values(Box::new(index.by_issuer("a\0b"))), [1]);
+        let got = index.range(SubjectId::FORMAT_ISSUER_SUBJECT, ..);
+        assert_eq!(values(Box::new(got)), [2, 1, 3]);
+    }
+}
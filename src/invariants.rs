@@ -0,0 +1,83 @@
+//! `invariants` module exposes the invariants this crate maintains, so that downstream CI and
+//! fuzz targets can check them with the rules of the crate version they depend on. Every check
+//! returns [`Error::InvariantViolation`] describing the first violation found.
+//!
+//! ```
+//! use subject_id::invariants;
+//! invariants::check_round_trip(r#"{"format":"email","email":"user@example.com"}"#).unwrap();
+//! ```
+
+use crate::{CanonicalPolicy, CaseFolding, Error, SubjectId};
+
+/// Check that parsing, serializing and parsing again is stable: input that parses as a
+/// [SubjectId] serializes to JSON that parses to an equal [SubjectId] and serializes to the same
+/// JSON. Input that does not parse trivially satisfies the invariant.
+pub fn check_round_trip(input: &str) -> Result<(), Error> {
+    let Ok(first) = serde_json::from_str::<SubjectId>(input) else {
+        return Ok(());
+    };
+    let json = serde_json::to_string(&first)?;
+    let second: SubjectId = serde_json::from_str(&json)
+        .map_err(|err| violation(format!("serialized form does not parse: {err}")))?;
+    if second != first {
+        return Err(violation(format!("{json} parses to a different value")));
+    }
+    if serde_json::to_string(&second)? != json {
+        return Err(violation(format!("{json} does not serialize to itself")));
+    }
+    Ok(())
+}
+
+/// Check that canonicalization is idempotent under every [CaseFolding], and that comparing the
+/// subject identifier with its canonical form is consistent with equality.
+pub fn check_canonical_idempotent(sub_id: &SubjectId) -> Result<(), Error> {
+    for case_folding in [
+        CaseFolding::Preserve,
+        CaseFolding::Domain,
+        CaseFolding::Full,
+    ] {
+        let policy = CanonicalPolicy { case_folding };
+        let once = sub_id.canonicalize(&policy);
+        let twice = once.canonicalize(&policy);
+        if twice != once {
+            return Err(violation(format!(
+                "canonicalization under {case_folding:?} is not idempotent: {once:?} became {twice:?}"
+            )));
+        }
+        if (once == *sub_id) != (once.cmp(sub_id).is_eq()) {
+            return Err(violation(format!(
+                "ordering of {sub_id:?} is inconsistent with equality"
+            )));
+        }
+    }
+    Ok(())
+}
+
+fn violation(reason: String) -> Error {
+    Error::InvariantViolation(reason)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{Aliases, Atomic};
+
+    #[test]
+    fn test_invariants() {
+        let inputs = [
+            r#"{"format":"phone_number","phone_number":"12065550100"}"#,
+            r#"{"format":"aliases","identifiers":[{"format":"email","email":"(c) U@Example.COM"}]}"#,
+            r#"{"format":"unknown"}"#,
+        ];
+        for input in inputs {
+            check_round_trip(input).unwrap();
+            if let Ok(sub_id) = serde_json::from_str(input) {
+                check_canonical_idempotent(&sub_id).unwrap();
+            }
+        }
+        let sub_id = SubjectId::Aliases(Aliases::from(vec![Atomic::Account {
+            uri: "ACCT:User@Example.COM".to_owned(),
+        }]));
+        check_canonical_idempotent(&sub_id).unwrap();
+    }
+}
@@ -0,0 +1,143 @@
+//! `kafka` module helps shipping subject identifiers through Kafka topics that are governed by a
+//! Confluent compatible schema registry. It exposes the JSON Schema of subject identifiers in the
+//! form expected by the registry, and (de)serializes subject identifiers with the registry wire
+//! framing: a zero magic byte, followed by the 4-byte big-endian schema ID, followed by the JSON
+//! payload. This module is available behind the `kafka` feature.
+
+use crate::{Error, SubjectId};
+use serde_json::{json, Value};
+
+/// Magic byte that starts every payload framed for the schema registry.
+const MAGIC_BYTE: u8 = 0;
+
+/// Length of the framing header: magic byte and schema ID.
+const HEADER_LEN: usize = 5;
+
+/// JSON Schema (draft-07) that every serialized [SubjectId] conforms to.
+///
+/// ```
+/// let schema = subject_id::kafka::json_schema();
+/// assert_eq!(schema["title"], "SubjectId");
+/// ```
+pub fn json_schema() -> Value {
+    let string = json!({ "type": "string", "minLength": 1 });
+    let atomic = |format: &str, members: &[&str]| {
+        let properties: serde_json::Map<String, Value> = members
+            .iter()
+            .map(|member| (member.to_string(), string.clone()))
+            .chain([("format".to_owned(), json!({ "const": format }))])
+            .collect();
+        let mut required = vec!["format"];
+        required.extend(members);
+        json!({
+            "type": "object",
+            "properties": properties,
+            "required": required,
+            "additionalProperties": false,
+        })
+    };
+    json!({
+        "$schema": "http://json-schema.org/draft-07/schema#",
+        "title": "SubjectId",
+        "definitions": {
+            "atomic": {
+                "oneOf": [
+                    atomic(SubjectId::FORMAT_ACCOUNT, &["uri"]),
+                    atomic(SubjectId::FORMAT_EMAIL, &["email"]),
+                    atomic(SubjectId::FORMAT_ISSUER_SUBJECT, &["issuer", "subject"]),
+                    atomic(SubjectId::FORMAT_OPAQUE, &["id"]),
+                    atomic(SubjectId::FORMAT_PHONE_NUMBER, &["phone_number"]),
+                    atomic(SubjectId::FORMAT_DID, &["url"]),
+                    atomic(SubjectId::FORMAT_URI, &["uri"]),
+                ],
+            },
+            "aliases": {
+                "type": "object",
+                "properties": {
+                    "format": { "const": SubjectId::FORMAT_ALIASES },
+                    "identifiers": {
+                        "type": "array",
+                        "items": { "$ref": "#/definitions/atomic" },
+                        "minItems": 1,
+                    },
+                },
+                "required": ["format", "identifiers"],
+                "additionalProperties": false,
+            },
+        },
+        "oneOf": [
+            { "$ref": "#/definitions/atomic" },
+            { "$ref": "#/definitions/aliases" },
+        ],
+    })
+}
+
+/// Request body to register [`json_schema`] with the schema registry, i.e. the payload of
+/// `POST /subjects/{subject}/versions`.
+///
+/// ```
+/// let body = subject_id::kafka::registry_schema();
+/// assert_eq!(body["schemaType"], "JSON");
+/// ```
+pub fn registry_schema() -> Value {
+    json!({
+        "schemaType": "JSON",
+        "schema": json_schema().to_string(),
+    })
+}
+
+impl SubjectId {
+    /// Serialize the [SubjectId] as JSON, framed for the schema registry with the given schema ID.
+    ///
+    /// ```
+    /// use subject_id::{Atomic, SubjectId};
+    /// let sub_id = SubjectId::Atomic(Atomic::Opaque { id: "1i3j4l".to_owned() });
+    /// let bytes = sub_id.to_registry_bytes(42);
+    /// assert_eq!(&bytes[..5], &[0, 0, 0, 0, 42]);
+    /// ```
+    pub fn to_registry_bytes(&self, schema_id: u32) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(64);
+        bytes.push(MAGIC_BYTE);
+        bytes.extend_from_slice(&schema_id.to_be_bytes());
+        serde_json::to_writer(&mut bytes, self).expect("subject identifiers always serialize");
+        bytes
+    }
+
+    /// Deserialize a [SubjectId] framed for the schema registry, returning the schema ID from the
+    /// framing header along with the subject identifier.
+    pub fn from_registry_bytes(bytes: &[u8]) -> Result<(u32, Self), Error> {
+        if bytes.len() < HEADER_LEN || bytes[0] != MAGIC_BYTE {
+            return Err(Error::InvalidRegistryFraming);
+        }
+        let schema_id = u32::from_be_bytes([bytes[1], bytes[2], bytes[3], bytes[4]]);
+        let sub_id = serde_json::from_slice(&bytes[HEADER_LEN..])?;
+        Ok((schema_id, sub_id))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Atomic;
+
+    #[test]
+    fn test_registry_round_trip() {
+        let sub_id = SubjectId::Atomic(Atomic::Email {
+            email: "user@example.com".to_owned(),
+        });
+        let bytes = sub_id.to_registry_bytes(0x01020304);
+        assert_eq!(&bytes[..HEADER_LEN], &[0, 1, 2, 3, 4]);
+        let (schema_id, got) = SubjectId::from_registry_bytes(&bytes).unwrap();
+        assert_eq!(schema_id, 0x01020304);
+        assert_eq!(got, sub_id);
+    }
+
+    #[test]
+    fn test_registry_invalid_framing() {
+        let cases: [&[u8]; 2] = [&[0, 0, 0], b"\x01\x00\x00\x00\x01{}"];
+        for bytes in cases {
+            let got = SubjectId::from_registry_bytes(bytes);
+            assert!(matches!(got, Err(Error::InvalidRegistryFraming)));
+        }
+    }
+}
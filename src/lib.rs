@@ -27,15 +27,94 @@
 //!
 //! [`SubjectID`]: https://datatracker.ietf.org/doc/html/draft-ietf-secevent-subject-identifiers
 
+#![forbid(unsafe_code)]
+
 use ::serde::{Deserialize, Serialize};
 
+pub use annotated::{AnnotatedSubjectId, Verification};
+pub use canonical::{CanonicalPolicy, CaseFolding};
+#[cfg(feature = "tokio-codec")]
+pub use codec::SubjectIdCodec;
+pub use complex::{ComplexSubject, ComplexSubjectBuilder, ComplexSubjectPolicy};
+pub use did::DidResolver;
+#[cfg(feature = "did-web")]
+pub use did::DidWebResolver;
 pub use e164::PhoneNumber;
+pub use email::EmailAddress;
 pub use error::Error;
+pub use explain::{Comparison, IdentifierTrace, MatchExplanation, MatchOutcome};
+pub use index::SubjectIdIndex;
+#[cfg(feature = "unicode-normalization")]
+pub use normalize::UnicodeNfc;
+pub use normalize::{EmailFolding, NormalizationPipeline, Normalizer, UriNormalization};
+pub use parse::{ParseErrHook, ParseOkHook, ParseOptions};
+pub use phone::{
+    NumberType, OfflinePhoneMetadata, PhoneMetadata, PhoneMetadataNormalizer,
+    PhoneMetadataProvider, PhonePolicy,
+};
+pub use pool::SubjectIdPool;
+#[cfg(feature = "round-trip")]
+pub use preserve::PreservedSubjectId;
+pub use pseudonym::PseudonymKey;
 pub use single::Atomic;
+pub use validate::{
+    serialize_validated, serialize_validated_with, OpaquePolicy, OpaqueShape, Validated,
+    ValidatedWith, ValidationPolicy,
+};
+#[cfg(feature = "dns")]
+pub use verify::MxVerifier;
+pub use verify::{EmailVerifier, SyntacticVerifier};
 
+pub mod adapters;
+mod annotated;
+#[cfg(feature = "arrow")]
+pub mod arrow;
+mod audit;
+mod canonical;
+#[cfg(feature = "tokio-codec")]
+mod codec;
+mod complex;
+mod did;
 mod e164;
+mod email;
+pub mod erasure;
 mod error;
+mod explain;
+pub mod export;
+mod flat;
+#[cfg(any(feature = "did-web", feature = "webfinger"))]
+mod http;
+mod index;
+#[cfg(feature = "test-util")]
+pub mod invariants;
+#[cfg(feature = "kafka")]
+pub mod kafka;
+#[cfg(feature = "log-kv")]
+mod log_kv;
+mod normalize;
+#[cfg(feature = "opentelemetry")]
+mod otel;
+mod parse;
+mod phone;
+mod pool;
+#[cfg(feature = "round-trip")]
+mod preserve;
+mod pseudonym;
+pub mod registry;
+#[cfg(feature = "json5")]
+mod relaxed;
+pub mod set;
+#[cfg(feature = "simd-json")]
+mod simd;
 mod single;
+mod size;
+pub mod ssf;
+#[cfg(feature = "ts")]
+mod ts;
+mod validate;
+mod verify;
+#[cfg(feature = "webfinger")]
+pub mod webfinger;
 
 /// SubjectID is the core type of the crate that defines subject identifier for Security Event Token
 /// (SET). Either a subject identifier has to be [Atomic] or [Aliases].
@@ -47,7 +126,8 @@ mod single;
 /// );
 /// println!("{:?}", sub_id);
 /// ```
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
 #[serde(untagged)]
 pub enum SubjectId {
     Atomic(Atomic),
@@ -60,7 +140,7 @@ pub enum SubjectId {
 /// identifiers they will recognize or support. This format is identified by the name "aliases".
 /// "aliases" Subject Identifiers MUST NOT be nested; i.e., the "identifiers" member of an "aliases"
 /// Subject Identifier MUST NOT contain a Subject Identifier in the "aliases" format.
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(tag = "format")]
 #[serde(rename = "aliases")]
 pub struct Aliases {
@@ -95,6 +175,82 @@ impl SubjectId {
             Self::Aliases(..) => Self::FORMAT_ALIASES,
         }
     }
+
+    /// Domain the [SubjectId] is scoped to, see [`Atomic::domain`]. For "aliases" subject
+    /// identifiers, it is the domain of the first identifier that carries one.
+    pub fn domain(&self) -> Option<&str> {
+        match self {
+            Self::Atomic(id) => id.domain(),
+            Self::Aliases(aliases) => aliases.identifiers.iter().find_map(Atomic::domain),
+        }
+    }
+
+    /// Keep only the identifiers whose format is one of the given formats, returning a new
+    /// [SubjectId]. An "aliases" subject identifier collapses to its atomic identifier when only
+    /// one remains; [`Error::NoIdentifiers`] is returned when none remain.
+    ///
+    /// ```
+    /// use subject_id::{Aliases, Atomic, SubjectId};
+    /// let sub_id = SubjectId::Aliases(Aliases::from(vec![
+    ///     Atomic::Email { email: "user@example.com".to_owned() },
+    ///     Atomic::PhoneNumber { phone_number: "+12065550100".parse().unwrap() },
+    /// ]));
+    /// let got = sub_id.retain_formats(&[SubjectId::FORMAT_EMAIL]).unwrap();
+    /// let want = SubjectId::Atomic(Atomic::Email { email: "user@example.com".to_owned() });
+    /// assert_eq!(got, want);
+    /// ```
+    pub fn retain_formats(&self, formats: &[&str]) -> Result<Self, Error> {
+        self.filter_formats(|format| formats.contains(&format))
+    }
+
+    /// Remove the identifiers whose format is one of the given formats, e.g. phone numbers before
+    /// forwarding events to a less-trusted party. See [`SubjectId::retain_formats`] for how the
+    /// result is built.
+    pub fn strip_formats(&self, formats: &[&str]) -> Result<Self, Error> {
+        self.filter_formats(|format| !formats.contains(&format))
+    }
+
+    fn filter_formats(&self, keep: impl Fn(&str) -> bool) -> Result<Self, Error> {
+        let mut identifiers: Vec<Atomic> = match self {
+            Self::Atomic(id) => vec![id.clone()],
+            Self::Aliases(aliases) => aliases.identifiers.clone(),
+        };
+        identifiers.retain(|id| keep(id.format()));
+        match identifiers.len() {
+            0 => Err(Error::NoIdentifiers),
+            1 => Ok(Self::Atomic(identifiers.remove(0))),
+            _ => Ok(Self::Aliases(Aliases { identifiers })),
+        }
+    }
+}
+
+impl Aliases {
+    /// Report the exact duplicates among the identifiers as pairs of indices `(i, j)` with
+    /// `i < j`, in ascending order. The "identifiers" member SHOULD NOT contain exact duplicates,
+    /// so transmitters may warn or clean up the list before emitting events.
+    ///
+    /// ```
+    /// use subject_id::{Aliases, Atomic};
+    /// let email = Atomic::Email { email: "user@example.com".to_owned() };
+    /// let aliases = Aliases::from(vec![email.clone(), email]);
+    /// assert_eq!(aliases.find_duplicates(), vec![(0, 1)]);
+    /// ```
+    pub fn find_duplicates(&self) -> Vec<(usize, usize)> {
+        duplicates(&self.identifiers)
+    }
+}
+
+/// Index pairs `(i, j)` with `i < j` of equal elements.
+fn duplicates<T: PartialEq>(items: &[T]) -> Vec<(usize, usize)> {
+    let mut pairs = Vec::new();
+    for (i, a) in items.iter().enumerate() {
+        for (j, b) in items.iter().enumerate().skip(i + 1) {
+            if a == b {
+                pairs.push((i, j));
+            }
+        }
+    }
+    pairs
 }
 
 impl From<Atomic> for SubjectId {
@@ -129,4 +285,44 @@ mod test {
         let want = SubjectId::Aliases(Aliases { identifiers });
         assert_eq!(got, want);
     }
+
+    #[test]
+    fn test_find_duplicates() {
+        let email = |email: &str| Atomic::Email {
+            email: email.to_owned(),
+        };
+        let aliases = Aliases::from(vec![
+            email("user@example.com"),
+            email("User@Example.com"),
+            email("user@example.com"),
+            email("user@example.com"),
+        ]);
+        assert_eq!(aliases.find_duplicates(), vec![(0, 2), (0, 3), (2, 3)]);
+        let policy = CanonicalPolicy::full_lowercase();
+        let want = vec![(0, 1), (0, 2), (0, 3), (1, 2), (1, 3), (2, 3)];
+        assert_eq!(aliases.find_canonical_duplicates(&policy), want);
+    }
+
+    #[test]
+    fn test_strip_formats() {
+        let phone = Atomic::PhoneNumber {
+            phone_number: "+12065550100".parse().unwrap(),
+        };
+        let email = Atomic::Email {
+            email: "user@example.com".to_owned(),
+        };
+        let opaque = Atomic::Opaque {
+            id: "1i3j4l".to_owned(),
+        };
+        let sub_id = SubjectId::Aliases(Aliases::from(vec![
+            phone.clone(),
+            email.clone(),
+            opaque.clone(),
+        ]));
+        let got = sub_id.strip_formats(&[SubjectId::FORMAT_PHONE_NUMBER]);
+        let want = SubjectId::Aliases(Aliases::from(vec![email, opaque]));
+        assert_eq!(got.unwrap(), want);
+        let got = SubjectId::Atomic(phone).strip_formats(&[SubjectId::FORMAT_PHONE_NUMBER]);
+        assert!(matches!(got, Err(Error::NoIdentifiers)));
+    }
 }
@@ -0,0 +1,53 @@
+//! `log_kv` module integrates subject identifiers with the structured logging of the [`log`]
+//! crate, for services that do not use `tracing`. A subject identifier logged as a key-value pair
+//! is captured as its masked audit rendering: the format and redacted member values. This module
+//! is available behind the `log-kv` feature.
+//!
+//! [`log`]: https://docs.rs/log
+//!
+//! ```
+//! use subject_id::{Atomic, SubjectId};
+//! let sub_id = SubjectId::Atomic(Atomic::Email { email: "user@example.com".to_owned() });
+//! log::info!(subject = sub_id; "session revoked");
+//! ```
+
+use crate::SubjectId;
+use log::kv::{ToValue, Value};
+use ref_cast::RefCast;
+use std::fmt::{Display, Formatter};
+
+impl ToValue for SubjectId {
+    fn to_value(&self) -> Value<'_> {
+        Value::from_display(Masked::ref_cast(self))
+    }
+}
+
+/// [Masked] displays a [SubjectId] as its masked audit rendering, see
+/// [`SubjectId::to_masked_audit_string`].
+#[derive(RefCast)]
+#[repr(transparent)]
+struct Masked(SubjectId);
+
+impl Display for Masked {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0.to_masked_audit_string())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Atomic;
+
+    #[test]
+    fn test_to_value() {
+        let sub_id = SubjectId::Atomic(Atomic::Email {
+            email: "user@example.com".to_owned(),
+        });
+        let value = sub_id.to_value();
+        assert_eq!(
+            value.to_string(),
+            r#"format=email email="u***@example.com""#
+        );
+    }
+}
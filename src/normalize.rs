@@ -0,0 +1,376 @@
+//! `normalize` module exposes normalization as a [NormalizationPipeline] of [Normalizer] steps.
+//! Each step rewrites atomic identifiers of the formats it knows about and leaves the others
+//! unchanged. Steps run in order, so a pipeline can be reordered, have steps disabled by name, or
+//! be extended with custom steps, e.g. to strip a tenant prefix from opaque identifiers.
+//!
+//! [`SubjectId::canonicalize`] runs the pipeline derived from its [CanonicalPolicy].
+
+use crate::{Aliases, Atomic, CanonicalPolicy, CaseFolding, EmailAddress, SubjectId};
+use std::fmt::Debug;
+
+/// [Normalizer] is a single step of a [NormalizationPipeline].
+///
+/// ```
+/// use subject_id::{Atomic, NormalizationPipeline, Normalizer};
+///
+/// #[derive(Debug)]
+/// struct TrimOpaque;
+///
+/// impl Normalizer for TrimOpaque {
+///     fn name(&self) -> &str {
+///         "trim_opaque"
+///     }
+///     fn normalize(&self, id: Atomic) -> Atomic {
+///         match id {
+///             Atomic::Opaque { id } => Atomic::Opaque { id: id.trim().to_owned() },
+///             id => id,
+///         }
+///     }
+/// }
+///
+/// let pipeline = NormalizationPipeline::empty().with_step(TrimOpaque);
+/// let got = pipeline.normalize_atomic(Atomic::Opaque { id: " 1i3j4l ".to_owned() });
+/// assert_eq!(got, Atomic::Opaque { id: "1i3j4l".to_owned() });
+/// ```
+pub trait Normalizer: Debug + Send + Sync {
+    /// Name of the step, used to disable it in a [NormalizationPipeline].
+    fn name(&self) -> &str;
+    /// Normalize the atomic identifier, returning it unchanged if the step does not apply.
+    fn normalize(&self, id: Atomic) -> Atomic;
+}
+
+/// [NormalizationPipeline] applies its [Normalizer] steps in order.
+///
+/// ```
+/// use subject_id::{Atomic, CanonicalPolicy, NormalizationPipeline};
+/// let mut pipeline = NormalizationPipeline::standard(&CanonicalPolicy::default());
+/// assert!(pipeline.disable("uri"));
+/// let id = Atomic::Uri { uri: "HTTPS://Example.COM/Path".to_owned() };
+/// assert_eq!(pipeline.normalize_atomic(id.clone()), id);
+/// ```
+#[derive(Debug)]
+pub struct NormalizationPipeline {
+    steps: Vec<Box<dyn Normalizer>>,
+}
+
+impl NormalizationPipeline {
+    /// Pipeline without any step, normalizing nothing.
+    pub fn empty() -> Self {
+        Self { steps: Vec::new() }
+    }
+
+    /// Pipeline with every built-in step: Unicode NFC (with the `unicode-normalization`
+    /// feature), email folding according to the policy and URI normalization. Phone numbers need
+    /// no step, since a parsed [`PhoneNumber`](crate::PhoneNumber) is already in E.164 form.
+    pub fn standard(policy: &CanonicalPolicy) -> Self {
+        let pipeline = Self::empty();
+        #[cfg(feature = "unicode-normalization")]
+        let pipeline = pipeline.with_step(UnicodeNfc);
+        pipeline
+            .with_step(EmailFolding::new(policy.case_folding))
+            .with_step(UriNormalization::default())
+    }
+
+    /// Append a step to the pipeline.
+    pub fn with_step(mut self, step: impl Normalizer + 'static) -> Self {
+        self.push(step);
+        self
+    }
+
+    /// Append a step to the pipeline.
+    pub fn push(&mut self, step: impl Normalizer + 'static) {
+        self.steps.push(Box::new(step));
+    }
+
+    /// Insert a step at the given position.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is greater than the number of steps.
+    pub fn insert(&mut self, index: usize, step: impl Normalizer + 'static) {
+        self.steps.insert(index, Box::new(step));
+    }
+
+    /// Remove every step with the given name, reporting whether any was removed.
+    pub fn disable(&mut self, name: &str) -> bool {
+        let len = self.steps.len();
+        self.steps.retain(|step| step.name() != name);
+        self.steps.len() != len
+    }
+
+    /// Move the step with the given name to the given position, reporting whether it was found.
+    /// The position is clamped to the end of the pipeline.
+    pub fn reorder(&mut self, name: &str, index: usize) -> bool {
+        let Some(from) = self.steps.iter().position(|step| step.name() == name) else {
+            return false;
+        };
+        let step = self.steps.remove(from);
+        self.steps.insert(index.min(self.steps.len()), step);
+        true
+    }
+
+    /// Names of the steps, in the order they are applied.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.steps.iter().map(|step| step.name())
+    }
+
+    /// Normalize every identifier of the [SubjectId].
+    pub fn normalize(&self, sub_id: &SubjectId) -> SubjectId {
+        match sub_id {
+            SubjectId::Atomic(id) => SubjectId::Atomic(self.normalize_atomic(id.clone())),
+            SubjectId::Aliases(aliases) => {
+                let identifiers = aliases
+                    .identifiers
+                    .iter()
+                    .map(|id| self.normalize_atomic(id.clone()))
+                    .collect();
+                SubjectId::Aliases(Aliases { identifiers })
+            }
+        }
+    }
+
+    /// Run the atomic identifier through every step.
+    pub fn normalize_atomic(&self, id: Atomic) -> Atomic {
+        self.steps.iter().fold(id, |id, step| step.normalize(id))
+    }
+
+    /// Run the atomic identifier through every step, also reporting the names of the steps that
+    /// changed it.
+    pub(crate) fn normalize_traced(&self, mut id: Atomic) -> (Atomic, Vec<String>) {
+        let mut applied = Vec::new();
+        for step in &self.steps {
+            let normalized = step.normalize(id.clone());
+            if normalized != id {
+                applied.push(step.name().to_owned());
+            }
+            id = normalized;
+        }
+        (id, applied)
+    }
+}
+
+/// The pipeline implementing [`SubjectId::canonicalize`], which only folds emails and "acct" URIs.
+impl From<&CanonicalPolicy> for NormalizationPipeline {
+    fn from(policy: &CanonicalPolicy) -> Self {
+        Self::empty().with_step(EmailFolding::new(policy.case_folding))
+    }
+}
+
+/// [UnicodeNfc] converts every member to Unicode Normalization Form C, so that visually
+/// identical identifiers entered with different compositions compare equal.
+#[cfg(feature = "unicode-normalization")]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct UnicodeNfc;
+
+#[cfg(feature = "unicode-normalization")]
+impl Normalizer for UnicodeNfc {
+    fn name(&self) -> &str {
+        "nfc"
+    }
+
+    fn normalize(&self, id: Atomic) -> Atomic {
+        use unicode_normalization::UnicodeNormalization;
+        let nfc = |s: String| s.nfc().collect::<String>();
+        match id {
+            Atomic::Account { uri } => Atomic::Account { uri: nfc(uri) },
+            Atomic::Email { email } => Atomic::Email { email: nfc(email) },
+            Atomic::IssuerSubject { issuer, subject } => Atomic::IssuerSubject {
+                issuer: nfc(issuer),
+                subject: nfc(subject),
+            },
+            Atomic::Opaque { id } => Atomic::Opaque { id: nfc(id) },
+            Atomic::Did { url } => Atomic::Did { url: nfc(url) },
+            Atomic::Uri { uri } => Atomic::Uri { uri: nfc(uri) },
+            id @ Atomic::PhoneNumber { .. } => id,
+        }
+    }
+}
+
+/// [EmailFolding] normalizes email addresses that parse as an [EmailAddress] (comments and
+/// unnecessary quotes are removed), then applies case folding to emails and "acct" URIs. The
+/// "acct" scheme is always lowercased.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct EmailFolding {
+    case_folding: CaseFolding,
+}
+
+impl EmailFolding {
+    /// Email folding with the given case folding.
+    pub fn new(case_folding: CaseFolding) -> Self {
+        Self { case_folding }
+    }
+}
+
+impl Normalizer for EmailFolding {
+    fn name(&self) -> &str {
+        "email"
+    }
+
+    fn normalize(&self, id: Atomic) -> Atomic {
+        match id {
+            Atomic::Email { email } => {
+                let email = match email.parse::<EmailAddress>() {
+                    Ok(addr) => addr.to_string(),
+                    Err(_) => email,
+                };
+                Atomic::Email {
+                    email: fold_address(&email, self.case_folding),
+                }
+            }
+            Atomic::Account { uri } => {
+                let uri = match uri.split_once(':') {
+                    Some((scheme, rest)) if scheme.eq_ignore_ascii_case("acct") => {
+                        "acct:".to_owned() + &fold_address(rest, self.case_folding)
+                    }
+                    _ => uri,
+                };
+                Atomic::Account { uri }
+            }
+            id => id,
+        }
+    }
+}
+
+/// [UriNormalization] lowercases the scheme and host of "uri" identifiers, which are
+/// case-insensitive by [`RFC3986`].
+///
+/// Issuers of "iss_sub" identifiers are left alone by default: the "iss" claim is compared as a
+/// case-sensitive string, so `HTTPS://Issuer.Example` and `https://issuer.example` are distinct
+/// issuers. [`UriNormalization::with_issuers`] opts into lowercasing them too, for receivers whose
+/// issuers are known to differ only by the case of their scheme and host.
+///
+/// [`RFC3986`]: https://www.rfc-editor.org/rfc/rfc3986#section-6.2.2.1
+#[derive(Clone, Copy, Debug, Default)]
+pub struct UriNormalization {
+    issuers: bool,
+}
+
+impl UriNormalization {
+    /// URI normalization also lowercasing the scheme and host of "iss_sub" issuers.
+    pub fn with_issuers() -> Self {
+        Self { issuers: true }
+    }
+}
+
+impl Normalizer for UriNormalization {
+    fn name(&self) -> &str {
+        "uri"
+    }
+
+    fn normalize(&self, id: Atomic) -> Atomic {
+        match id {
+            Atomic::Uri { uri } => Atomic::Uri {
+                uri: normalize_uri(&uri),
+            },
+            Atomic::IssuerSubject { issuer, subject } if self.issuers => Atomic::IssuerSubject {
+                issuer: normalize_uri(&issuer),
+                subject,
+            },
+            id => id,
+        }
+    }
+}
+
+/// Lowercase the scheme and host of the URI, keeping everything else.
+fn normalize_uri(uri: &str) -> String {
+    let Some((scheme, rest)) = uri.split_once(':') else {
+        return uri.to_owned();
+    };
+    let mut normalized = scheme.to_ascii_lowercase() + ":" + rest;
+    if let Some(host) = crate::single::uri_host(uri) {
+        let start = host.as_ptr() as usize - uri.as_ptr() as usize;
+        let end = start + host.len();
+        normalized.replace_range(start..end, &host.to_lowercase());
+    }
+    normalized
+}
+
+/// Apply case folding to an address of the form `local@domain`.
+pub(crate) fn fold_address(address: &str, case_folding: CaseFolding) -> String {
+    match (case_folding, address.rsplit_once('@')) {
+        (CaseFolding::Preserve, _) => address.to_owned(),
+        (CaseFolding::Domain, Some((local, domain))) => {
+            local.to_owned() + "@" + &domain.to_lowercase()
+        }
+        (CaseFolding::Domain, None) => address.to_owned(),
+        (CaseFolding::Full, _) => address.to_lowercase(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_normalize_uri() {
+        let cases = [
+            ("HTTPS://Example.COM/Path?Q", "https://example.com/Path?Q"),
+            (
+                "https://User@Example.COM:8443/",
+                "https://User@example.com:8443/",
+            ),
+            ("URN:Example:Thing", "urn:Example:Thing"),
+            ("no-scheme", "no-scheme"),
+        ];
+        for (input, want) in cases {
+            assert_eq!(normalize_uri(input), want, "{input}");
+        }
+    }
+
+    #[test]
+    fn test_pipeline() {
+        let mut pipeline = NormalizationPipeline::standard(&CanonicalPolicy::default());
+        let sub_id = SubjectId::Aliases(Aliases::from(vec![
+            Atomic::Email {
+                email: "User@Example.COM".to_owned(),
+            },
+            Atomic::IssuerSubject {
+                issuer: "HTTPS://Issuer.Example.COM".to_owned(),
+                subject: "AbC".to_owned(),
+            },
+        ]));
+        let want = SubjectId::Aliases(Aliases::from(vec![
+            Atomic::Email {
+                email: "User@example.com".to_owned(),
+            },
+            Atomic::IssuerSubject {
+                issuer: "HTTPS://Issuer.Example.COM".to_owned(),
+                subject: "AbC".to_owned(),
+            },
+        ]));
+        assert_eq!(pipeline.normalize(&sub_id), want);
+        assert!(!pipeline.names().any(|name| name == "phone"));
+
+        assert!(pipeline.reorder("uri", 0));
+        assert_eq!(pipeline.names().next(), Some("uri"));
+        assert!(pipeline.disable("email"));
+        assert!(!pipeline.disable("email"));
+        assert!(!pipeline.names().any(|name| name == "email"));
+    }
+
+    #[test]
+    fn test_uri_normalization_issuers() {
+        let id = Atomic::IssuerSubject {
+            issuer: "HTTPS://Issuer.Example.COM/Tenant".to_owned(),
+            subject: "AbC".to_owned(),
+        };
+        assert_eq!(UriNormalization::default().normalize(id.clone()), id);
+        let want = Atomic::IssuerSubject {
+            issuer: "https://issuer.example.com/Tenant".to_owned(),
+            subject: "AbC".to_owned(),
+        };
+        assert_eq!(UriNormalization::with_issuers().normalize(id), want);
+    }
+
+    #[cfg(feature = "unicode-normalization")]
+    #[test]
+    fn test_unicode_nfc() {
+        let id = Atomic::Opaque {
+            id: "Cafe\u{301}".to_owned(),
+        };
+        let want = Atomic::Opaque {
+            id: "Caf\u{e9}".to_owned(),
+        };
+        assert_eq!(UnicodeNfc.normalize(id), want);
+    }
+}
@@ -0,0 +1,56 @@
+//! `otel` module converts subject identifiers into [`OpenTelemetry`] span and log attributes, so
+//! that tracing of event processing reports subjects consistently across services. This module is
+//! available behind the `opentelemetry` feature.
+//!
+//! [`OpenTelemetry`]: https://opentelemetry.io/
+
+use crate::{PseudonymKey, SubjectId};
+use opentelemetry::KeyValue;
+
+const ATTR_FORMAT: &str = "subject.format";
+const ATTR_DOMAIN: &str = "subject.domain";
+const ATTR_FINGERPRINT: &str = "subject.fingerprint";
+
+impl SubjectId {
+    /// Convert the [SubjectId] into OpenTelemetry attributes. The identifier itself is redacted:
+    /// only the following attributes are reported, the fingerprint keyed with the given key.
+    ///
+    /// - `subject.format`: the subject identifier format, see [`SubjectId::format`].
+    /// - `subject.domain`: the domain of the subject, if any, see [`SubjectId::domain`].
+    /// - `subject.fingerprint`: the fingerprint of the subject, see [`SubjectId::fingerprint`].
+    ///
+    /// ```
+    /// use subject_id::{Atomic, PseudonymKey, SubjectId};
+    /// let key = PseudonymKey::new("otel", b"secret".to_vec());
+    /// let sub_id = SubjectId::Atomic(Atomic::Email { email: "user@example.com".to_owned() });
+    /// let attributes = sub_id.otel_attributes(&key);
+    /// assert_eq!(attributes.len(), 3);
+    /// ```
+    pub fn otel_attributes(&self, key: &PseudonymKey) -> Vec<KeyValue> {
+        let mut attributes = vec![KeyValue::new(ATTR_FORMAT, self.format())];
+        if let Some(domain) = self.domain() {
+            attributes.push(KeyValue::new(ATTR_DOMAIN, domain.to_owned()));
+        }
+        attributes.push(KeyValue::new(ATTR_FINGERPRINT, self.fingerprint(key)));
+        attributes
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Atomic;
+
+    #[test]
+    fn test_otel_attributes() {
+        let key = PseudonymKey::new("otel", b"secret".to_vec());
+        let sub_id = SubjectId::Atomic(Atomic::Opaque {
+            id: "1i3j4l".to_owned(),
+        });
+        let want = vec![
+            KeyValue::new(ATTR_FORMAT, "opaque"),
+            KeyValue::new(ATTR_FINGERPRINT, sub_id.fingerprint(&key)),
+        ];
+        assert_eq!(sub_id.otel_attributes(&key), want);
+    }
+}
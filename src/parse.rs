@@ -0,0 +1,260 @@
+//! `parse` module parses subject identifiers from raw input, such as exported event files, whose
+//! encoding is not under the control of the receiver. Parsing is configured with [ParseOptions],
+//! which also carries the optional hooks observing every parse, e.g. for telemetry or sampling.
+//!
+//! The hooks only fire for [`SubjectId::parse_with`] and [`SubjectId::from_reader_with`] (and
+//! [`SubjectId::from_reader`], which uses the default options without hooks). The other entry
+//! points take no [ParseOptions] and never fire them: serde deserialization, `from_json5`,
+//! `from_simd_json` and `from_simd_tape`, the `SubjectIdCodec` decoder, `from_flat_map` and
+//! `from_env`, `from_registry_bytes`, `PreservedSubjectId::parse` and the Arrow conversions.
+
+use crate::{Error, SubjectId};
+use serde::Deserialize;
+use std::fmt::{Debug, Formatter};
+use std::io::Read;
+use std::sync::Arc;
+
+const BOM_UTF8: &[u8] = b"\xEF\xBB\xBF";
+const BOM_UTF16LE: &[u8] = b"\xFF\xFE";
+const BOM_UTF16BE: &[u8] = b"\xFE\xFF";
+
+/// Hook called with every successfully parsed [SubjectId].
+pub type ParseOkHook = Arc<dyn Fn(&SubjectId) + Send + Sync>;
+
+/// Hook called with every parse error, along with the "format" member of the input if it could
+/// be read.
+pub type ParseErrHook = Arc<dyn Fn(Option<&str>, &Error) + Send + Sync>;
+
+/// [ParseOptions] configures how [`SubjectId::parse_with`] and [`SubjectId::from_reader_with`]
+/// handle their input.
+///
+/// ```
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+/// use std::sync::Arc;
+/// use subject_id::{ParseOptions, SubjectId};
+///
+/// let errors = Arc::new(AtomicUsize::new(0));
+/// let counter = errors.clone();
+/// let options = ParseOptions::default().on_parse_err(move |format_hint, _| {
+///     assert_eq!(format_hint, Some("email"));
+///     counter.fetch_add(1, Ordering::Relaxed);
+/// });
+/// let input = br#"{"format":"email","uri":"acct:user@example.com"}"#;
+/// assert!(SubjectId::parse_with(input, &options).is_err());
+/// assert_eq!(errors.load(Ordering::Relaxed), 1);
+/// ```
+#[derive(Clone, Default)]
+pub struct ParseOptions {
+    /// Transcode input starting with a UTF-16 byte order mark (little or big endian) to UTF-8.
+    /// Otherwise, such input is rejected with [`Error::Utf16NotEnabled`].
+    pub transcode_utf16: bool,
+    /// Hook called after every successful parse by [`SubjectId::parse_with`] and
+    /// [`SubjectId::from_reader_with`]; other entry points do not fire it.
+    pub on_parse_ok: Option<ParseOkHook>,
+    /// Hook called after every failed parse by [`SubjectId::parse_with`] and
+    /// [`SubjectId::from_reader_with`], including failures to read or decode the input; other
+    /// entry points do not fire it.
+    pub on_parse_err: Option<ParseErrHook>,
+}
+
+impl ParseOptions {
+    /// Set the hook called after every successful parse.
+    pub fn on_parse_ok(mut self, hook: impl Fn(&SubjectId) + Send + Sync + 'static) -> Self {
+        self.on_parse_ok = Some(Arc::new(hook));
+        self
+    }
+
+    /// Set the hook called after every failed parse.
+    pub fn on_parse_err(
+        mut self,
+        hook: impl Fn(Option<&str>, &Error) + Send + Sync + 'static,
+    ) -> Self {
+        self.on_parse_err = Some(Arc::new(hook));
+        self
+    }
+
+    fn observe(&self, result: &Result<SubjectId, Error>, format_hint: Option<&str>) {
+        match (result, &self.on_parse_ok, &self.on_parse_err) {
+            (Ok(sub_id), Some(hook), _) => hook(sub_id),
+            (Err(err), _, Some(hook)) => hook(format_hint, err),
+            _ => {}
+        }
+    }
+}
+
+impl Debug for ParseOptions {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ParseOptions")
+            .field("transcode_utf16", &self.transcode_utf16)
+            .field("on_parse_ok", &self.on_parse_ok.is_some())
+            .field("on_parse_err", &self.on_parse_err.is_some())
+            .finish()
+    }
+}
+
+impl SubjectId {
+    /// Parse a [SubjectId] from a reader with the default [ParseOptions]. A leading UTF-8 byte
+    /// order mark is skipped, and input that is not valid UTF-8 is rejected with
+    /// [`Error::InvalidUtf8`].
+    ///
+    /// ```
+    /// use subject_id::SubjectId;
+    /// let input = b"\xEF\xBB\xBF{\"format\":\"opaque\",\"id\":\"1i3j4l\"}";
+    /// let sub_id = SubjectId::from_reader(&input[..]).unwrap();
+    /// assert_eq!(sub_id.format(), SubjectId::FORMAT_OPAQUE);
+    /// ```
+    pub fn from_reader(reader: impl Read) -> Result<Self, Error> {
+        Self::from_reader_with(reader, &ParseOptions::default())
+    }
+
+    /// Parse a [SubjectId] from a reader with the given [ParseOptions].
+    pub fn from_reader_with(mut reader: impl Read, options: &ParseOptions) -> Result<Self, Error> {
+        let mut input = Vec::new();
+        if let Err(err) = reader.read_to_end(&mut input) {
+            let result = Err(err.into());
+            options.observe(&result, None);
+            return result;
+        }
+        Self::parse_with(&input, options)
+    }
+
+    /// Parse a [SubjectId] from raw bytes with the given [ParseOptions].
+    pub fn parse_with(input: &[u8], options: &ParseOptions) -> Result<Self, Error> {
+        let text = match decode(input, options) {
+            Ok(text) => text,
+            Err(err) => {
+                let result = Err(err);
+                options.observe(&result, None);
+                return result;
+            }
+        };
+        let result = serde_json::from_str(&text).map_err(Error::from);
+        let format_hint = match &result {
+            Ok(_) => None,
+            Err(_) => format_hint(&text),
+        };
+        options.observe(&result, format_hint.as_deref());
+        result
+    }
+}
+
+/// Read the "format" member of a JSON object, ignoring the other members.
+fn format_hint(text: &str) -> Option<String> {
+    #[derive(Deserialize)]
+    struct Hint {
+        format: String,
+    }
+    serde_json::from_str::<Hint>(text)
+        .ok()
+        .map(|hint| hint.format)
+}
+
+/// Decode the input to UTF-8 text, handling byte order marks.
+fn decode(input: &[u8], options: &ParseOptions) -> Result<String, Error> {
+    let utf16 = match (
+        input.strip_prefix(BOM_UTF16LE),
+        input.strip_prefix(BOM_UTF16BE),
+    ) {
+        (Some(rest), _) => Some((rest, false)),
+        (_, Some(rest)) => Some((rest, true)),
+        _ => None,
+    };
+    if let Some((rest, big_endian)) = utf16 {
+        if !options.transcode_utf16 {
+            return Err(Error::Utf16NotEnabled);
+        }
+        return decode_utf16(rest, big_endian);
+    }
+    // Offsets reported in errors count the byte order mark, as they index the original input.
+    let (bom_len, input) = match input.strip_prefix(BOM_UTF8) {
+        Some(rest) => (BOM_UTF8.len(), rest),
+        None => (0, input),
+    };
+    match std::str::from_utf8(input) {
+        Ok(text) => Ok(text.to_owned()),
+        Err(err) => Err(Error::InvalidUtf8(bom_len + err.valid_up_to())),
+    }
+}
+
+fn decode_utf16(input: &[u8], big_endian: bool) -> Result<String, Error> {
+    if !input.len().is_multiple_of(2) {
+        return Err(Error::InvalidUtf16);
+    }
+    let units = input.chunks_exact(2).map(|b| match big_endian {
+        true => u16::from_be_bytes([b[0], b[1]]),
+        false => u16::from_le_bytes([b[0], b[1]]),
+    });
+    char::decode_utf16(units)
+        .collect::<Result<String, _>>()
+        .map_err(|_| Error::InvalidUtf16)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Atomic;
+
+    const JSON: &str = r#"{"format":"email","email":"jürgen@example.com"}"#;
+
+    fn want() -> SubjectId {
+        SubjectId::Atomic(Atomic::Email {
+            email: "jürgen@example.com".to_owned(),
+        })
+    }
+
+    fn utf16(bom: &[u8], to_bytes: fn(u16) -> [u8; 2]) -> Vec<u8> {
+        let mut input = bom.to_vec();
+        input.extend(JSON.encode_utf16().flat_map(to_bytes));
+        input
+    }
+
+    #[test]
+    fn test_from_reader_utf8() {
+        let mut input = BOM_UTF8.to_vec();
+        input.extend_from_slice(JSON.as_bytes());
+        assert_eq!(SubjectId::from_reader(&input[..]).unwrap(), want());
+        assert_eq!(SubjectId::from_reader(JSON.as_bytes()).unwrap(), want());
+        let got = SubjectId::from_reader(&b"{\"format\":\"opaque\",\"id\":\"\xC3\x28\"}"[..]);
+        assert!(matches!(got, Err(Error::InvalidUtf8(25))));
+        let mut input = BOM_UTF8.to_vec();
+        input.extend_from_slice(b"{\"format\":\"opaque\",\"id\":\"\xC3\x28\"}");
+        let got = SubjectId::from_reader(&input[..]);
+        assert!(matches!(got, Err(Error::InvalidUtf8(28))));
+    }
+
+    #[test]
+    fn test_from_reader_utf16() {
+        let options = ParseOptions {
+            transcode_utf16: true,
+            ..Default::default()
+        };
+        for input in [
+            utf16(BOM_UTF16LE, u16::to_le_bytes),
+            utf16(BOM_UTF16BE, u16::to_be_bytes),
+        ] {
+            let got = SubjectId::from_reader_with(&input[..], &options).unwrap();
+            assert_eq!(got, want());
+            let got = SubjectId::from_reader(&input[..]);
+            assert!(matches!(got, Err(Error::Utf16NotEnabled)));
+        }
+        let input = [0xFF, 0xFE, 0x00, 0xD8];
+        let got = SubjectId::parse_with(&input, &options);
+        assert!(matches!(got, Err(Error::InvalidUtf16)));
+    }
+
+    #[test]
+    fn test_hooks() {
+        use std::sync::Mutex;
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let (ok, err) = (seen.clone(), seen.clone());
+        let options = ParseOptions::default()
+            .on_parse_ok(move |sub_id| ok.lock().unwrap().push(sub_id.format().to_owned()))
+            .on_parse_err(move |hint, _| err.lock().unwrap().push(format!("err {hint:?}")));
+        assert!(SubjectId::parse_with(JSON.as_bytes(), &options).is_ok());
+        assert!(SubjectId::parse_with(br#"{"format":"did"}"#, &options).is_err());
+        assert!(SubjectId::parse_with(b"\xFF\xFE{}", &options).is_err());
+        assert!(SubjectId::parse_with(b"[]", &options).is_err());
+        let want = ["email", "err Some(\"did\")", "err None", "err None"];
+        assert_eq!(*seen.lock().unwrap(), want);
+    }
+}
@@ -0,0 +1,418 @@
+//! `phone` module looks up metadata of phone numbers (region, number type and validity) through
+//! a pluggable [PhoneMetadataProvider], so that receivers can enrich phone subjects or reject
+//! them by policy. [OfflinePhoneMetadata] is a small built-in table covering common country
+//! calling codes; applications needing full coverage plug in a provider backed by a complete
+//! numbering plan database.
+//!
+//! Providers are consulted by [`Atomic::validate_with`] through [PhonePolicy], and by
+//! [PhoneMetadataNormalizer] during normalization.
+
+use crate::{Atomic, Error, Normalizer, PhoneNumber, SubjectId};
+use std::fmt::Debug;
+use std::sync::Arc;
+
+/// [PhoneMetadata] describes a phone number.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PhoneMetadata {
+    /// Country calling code, without '+'.
+    pub country_code: String,
+    /// ISO 3166-1 alpha-2 code of the region the number belongs to.
+    pub region: String,
+    /// Kind of line the number is assigned to.
+    pub number_type: NumberType,
+    /// Whether the number is valid according to the numbering plan of the region.
+    pub valid: bool,
+    /// National trunk prefix that is dialed before the national number domestically, and must
+    /// not appear after the country calling code, e.g. "0".
+    pub trunk_prefix: Option<String>,
+}
+
+/// [NumberType] is the kind of line a phone number is assigned to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum NumberType {
+    /// Fixed (landline) number.
+    FixedLine,
+    /// Mobile number.
+    Mobile,
+    /// Number that is either fixed or mobile, where the numbering plan does not tell them apart.
+    FixedLineOrMobile,
+    /// Toll-free number.
+    TollFree,
+    /// Kind not known to the provider.
+    Unknown,
+}
+
+/// [PhoneMetadataProvider] looks up the metadata of phone numbers.
+pub trait PhoneMetadataProvider: Debug + Send + Sync {
+    /// Metadata of the number, or `None` if its country calling code is unknown.
+    fn lookup(&self, number: &PhoneNumber) -> Option<PhoneMetadata>;
+}
+
+/// [OfflinePhoneMetadata] is the built-in provider, backed by a table of country calling codes
+/// with the length of their national numbers and their mobile and toll-free prefixes. Numbers of
+/// the North American Numbering Plan are reported as "US", since the plan does not tell its
+/// countries apart by prefix.
+///
+/// ```
+/// use subject_id::{NumberType, OfflinePhoneMetadata, PhoneMetadataProvider};
+/// let number = "+447911123456".parse().unwrap();
+/// let metadata = OfflinePhoneMetadata.lookup(&number).unwrap();
+/// assert_eq!(metadata.region, "GB");
+/// assert_eq!(metadata.number_type, NumberType::Mobile);
+/// assert!(metadata.valid);
+/// ```
+#[derive(Clone, Copy, Debug, Default)]
+pub struct OfflinePhoneMetadata;
+
+/// Numbering plan of a country calling code.
+struct Plan {
+    country_code: &'static str,
+    region: &'static str,
+    trunk_prefix: Option<&'static str>,
+    lengths: (usize, usize),
+    mobile: &'static [&'static str],
+    toll_free: &'static [&'static str],
+    toll_free_lengths: (usize, usize),
+}
+
+const PLANS: &[Plan] = &[
+    Plan {
+        country_code: "1",
+        region: "US",
+        trunk_prefix: None,
+        lengths: (10, 10),
+        mobile: &[],
+        toll_free: &["800", "833", "844", "855", "866", "877", "888"],
+        toll_free_lengths: (10, 10),
+    },
+    Plan {
+        country_code: "7",
+        region: "RU",
+        // The trunk prefix "8" is also the first digit of national numbers, e.g. 812 for
+        // St Petersburg, so a trunk prefix cannot be told apart after the country code.
+        trunk_prefix: None,
+        lengths: (10, 10),
+        mobile: &["9"],
+        toll_free: &["800"],
+        toll_free_lengths: (10, 10),
+    },
+    Plan {
+        country_code: "33",
+        region: "FR",
+        trunk_prefix: Some("0"),
+        lengths: (9, 9),
+        mobile: &["6", "7"],
+        toll_free: &["80"],
+        toll_free_lengths: (9, 9),
+    },
+    Plan {
+        country_code: "44",
+        region: "GB",
+        trunk_prefix: Some("0"),
+        lengths: (9, 10),
+        mobile: &["7"],
+        toll_free: &["800", "808"],
+        toll_free_lengths: (9, 10),
+    },
+    Plan {
+        country_code: "49",
+        region: "DE",
+        trunk_prefix: Some("0"),
+        lengths: (6, 13),
+        mobile: &["15", "16", "17"],
+        toll_free: &["800"],
+        toll_free_lengths: (10, 10),
+    },
+    Plan {
+        country_code: "61",
+        region: "AU",
+        trunk_prefix: Some("0"),
+        lengths: (9, 9),
+        mobile: &["4"],
+        toll_free: &["1800"],
+        toll_free_lengths: (10, 10),
+    },
+    Plan {
+        country_code: "81",
+        region: "JP",
+        trunk_prefix: Some("0"),
+        lengths: (9, 10),
+        mobile: &["70", "80", "90"],
+        toll_free: &["120", "800"],
+        toll_free_lengths: (9, 10),
+    },
+    Plan {
+        country_code: "86",
+        region: "CN",
+        trunk_prefix: Some("0"),
+        lengths: (10, 11),
+        mobile: &["13", "14", "15", "16", "17", "18", "19"],
+        toll_free: &["400", "800"],
+        toll_free_lengths: (10, 10),
+    },
+    Plan {
+        country_code: "91",
+        region: "IN",
+        trunk_prefix: Some("0"),
+        lengths: (10, 10),
+        mobile: &["6", "7", "8", "9"],
+        toll_free: &["1800"],
+        toll_free_lengths: (10, 11),
+    },
+];
+
+impl PhoneMetadataProvider for OfflinePhoneMetadata {
+    fn lookup(&self, number: &PhoneNumber) -> Option<PhoneMetadata> {
+        let digits = number.as_str().trim_start_matches('+');
+        // Country calling codes are prefix-free, so at most one plan matches.
+        let plan = PLANS
+            .iter()
+            .find(|plan| digits.starts_with(plan.country_code))?;
+        let national = &digits[plan.country_code.len()..];
+        let starts_with = |prefixes: &[&str]| prefixes.iter().any(|p| national.starts_with(p));
+        let number_type = if starts_with(plan.toll_free) {
+            NumberType::TollFree
+        } else if starts_with(plan.mobile) {
+            NumberType::Mobile
+        } else if plan.mobile.is_empty() {
+            NumberType::FixedLineOrMobile
+        } else {
+            NumberType::FixedLine
+        };
+        let (min, max) = match number_type {
+            NumberType::TollFree => plan.toll_free_lengths,
+            _ => plan.lengths,
+        };
+        let trunk = plan.trunk_prefix.is_some_and(|p| national.starts_with(p));
+        Some(PhoneMetadata {
+            country_code: plan.country_code.to_owned(),
+            region: plan.region.to_owned(),
+            number_type,
+            valid: (min..=max).contains(&national.len()) && !trunk,
+            trunk_prefix: plan.trunk_prefix.map(str::to_owned),
+        })
+    }
+}
+
+/// [PhonePolicy] restricts "phone_number" identifiers using the metadata of its provider. The
+/// default policy has no provider and accepts every E.164 number. Numbers the provider does not
+/// know fail every restriction.
+#[derive(Clone, Debug, Default)]
+pub struct PhonePolicy {
+    /// Provider of the metadata; without a provider, no restriction applies.
+    pub provider: Option<Arc<dyn PhoneMetadataProvider>>,
+    /// Require numbers to be valid in their numbering plan.
+    pub require_valid: bool,
+    /// Allowed regions, if restricted.
+    pub allowed_regions: Option<Vec<String>>,
+    /// Allowed number types, if restricted.
+    pub allowed_types: Option<Vec<NumberType>>,
+}
+
+impl PhonePolicy {
+    /// Check the phone number against the policy.
+    pub fn check(&self, number: &PhoneNumber) -> Result<(), Error> {
+        let Some(provider) = &self.provider else {
+            return Ok(());
+        };
+        let restricted =
+            self.require_valid || self.allowed_regions.is_some() || self.allowed_types.is_some();
+        let Some(metadata) = provider.lookup(number) else {
+            return match restricted {
+                true => Err(invalid("unknown country calling code")),
+                false => Ok(()),
+            };
+        };
+        if self.require_valid && !metadata.valid {
+            return Err(invalid("not valid in its numbering plan"));
+        }
+        if let Some(regions) = &self.allowed_regions {
+            if !regions.contains(&metadata.region) {
+                return Err(invalid(format!(
+                    "region {} is not allowed",
+                    metadata.region
+                )));
+            }
+        }
+        if let Some(types) = &self.allowed_types {
+            if !types.contains(&metadata.number_type) {
+                return Err(invalid(format!(
+                    "{:?} is not allowed",
+                    metadata.number_type
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+fn invalid(reason: impl Into<String>) -> Error {
+    Error::InvalidSubject {
+        format: SubjectId::FORMAT_PHONE_NUMBER,
+        reason: reason.into(),
+    }
+}
+
+/// [PhoneMetadataNormalizer] removes a national trunk prefix written after the country calling
+/// code, e.g. `+44 (0)20 7946 0000` entered as `+4402079460000`, according to its provider.
+///
+/// The provider resolves a country calling code shared by several regions to a single region,
+/// e.g. +7 numbers of Kazakhstan to RU and +1 numbers of Canada to US with
+/// [OfflinePhoneMetadata], so the trunk prefix removed is the one of that region.
+#[derive(Clone, Debug)]
+pub struct PhoneMetadataNormalizer {
+    provider: Arc<dyn PhoneMetadataProvider>,
+}
+
+impl PhoneMetadataNormalizer {
+    /// Normalizer consulting the provider.
+    pub fn new(provider: Arc<dyn PhoneMetadataProvider>) -> Self {
+        Self { provider }
+    }
+
+    /// Remove the trunk prefix of the phone number, returning it unchanged if the provider does
+    /// not know it or its region has no trunk prefix. Fails if the country calling code reported
+    /// by the provider is not a prefix of the number.
+    pub fn strip_trunk_prefix(&self, phone_number: &PhoneNumber) -> Result<PhoneNumber, Error> {
+        let Some(metadata) = self.provider.lookup(phone_number) else {
+            return Ok(phone_number.clone());
+        };
+        let Some(trunk) = &metadata.trunk_prefix else {
+            return Ok(phone_number.clone());
+        };
+        let national = phone_number
+            .as_str()
+            .trim_start_matches('+')
+            .strip_prefix(metadata.country_code.as_str())
+            .ok_or_else(|| {
+                invalid(format!(
+                    "provider reported country calling code {} of another number",
+                    metadata.country_code
+                ))
+            })?;
+        match national.strip_prefix(trunk.as_str()) {
+            Some(rest) => format!("+{}{rest}", metadata.country_code).parse(),
+            None => Ok(phone_number.clone()),
+        }
+    }
+}
+
+impl Normalizer for PhoneMetadataNormalizer {
+    fn name(&self) -> &str {
+        "phone_metadata"
+    }
+
+    fn normalize(&self, id: Atomic) -> Atomic {
+        let Atomic::PhoneNumber { phone_number } = &id else {
+            return id;
+        };
+        match self.strip_trunk_prefix(phone_number) {
+            Ok(phone_number) => Atomic::PhoneNumber { phone_number },
+            Err(_) => id,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ValidationPolicy;
+
+    fn phone(number: &str) -> Atomic {
+        Atomic::PhoneNumber {
+            phone_number: number.parse().unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_offline_lookup() {
+        let cases = [
+            ("+12065550100", "US", NumberType::FixedLineOrMobile, true),
+            ("+18005550100", "US", NumberType::TollFree, true),
+            ("+33612345678", "FR", NumberType::Mobile, true),
+            ("+4402079460000", "GB", NumberType::FixedLine, false),
+            ("+78121234567", "RU", NumberType::FixedLine, true),
+            ("+78001234567", "RU", NumberType::TollFree, true),
+            ("+611800123456", "AU", NumberType::TollFree, true),
+            ("+61180012345", "AU", NumberType::TollFree, false),
+            ("+919876543210", "IN", NumberType::Mobile, true),
+            ("+9118001234567", "IN", NumberType::TollFree, true),
+        ];
+        for (number, region, number_type, valid) in cases {
+            let metadata = OfflinePhoneMetadata
+                .lookup(&number.parse().unwrap())
+                .unwrap();
+            assert_eq!(metadata.region, region, "{number}");
+            assert_eq!(metadata.number_type, number_type, "{number}");
+            assert_eq!(metadata.valid, valid, "{number}");
+        }
+        assert_eq!(
+            OfflinePhoneMetadata.lookup(&"+999123".parse().unwrap()),
+            None
+        );
+    }
+
+    #[test]
+    fn test_phone_policy() {
+        let policy = ValidationPolicy {
+            phone: PhonePolicy {
+                provider: Some(Arc::new(OfflinePhoneMetadata)),
+                require_valid: true,
+                allowed_regions: Some(vec!["GB".to_owned(), "FR".to_owned()]),
+                allowed_types: Some(vec![NumberType::Mobile]),
+            },
+            ..Default::default()
+        };
+        assert!(phone("+447911123456").validate_with(&policy).is_ok());
+        for number in ["+12065550100", "+442079460000", "+4479111234", "+999123"] {
+            assert!(phone(number).validate_with(&policy).is_err(), "{number}");
+        }
+        assert!(phone("+999123").validate().is_ok());
+    }
+
+    #[test]
+    fn test_normalizer() {
+        let normalizer = PhoneMetadataNormalizer::new(Arc::new(OfflinePhoneMetadata));
+        let cases = [
+            ("+4402079460000", "+442079460000"),
+            ("+442079460000", "+442079460000"),
+            ("+12065550100", "+12065550100"),
+            ("+78121234567", "+78121234567"),
+        ];
+        for (input, want) in cases {
+            assert_eq!(normalizer.normalize(phone(input)), phone(want));
+        }
+    }
+
+    #[derive(Debug)]
+    struct WrongCountryCode;
+
+    impl PhoneMetadataProvider for WrongCountryCode {
+        fn lookup(&self, _: &PhoneNumber) -> Option<PhoneMetadata> {
+            Some(PhoneMetadata {
+                country_code: "4412".to_owned(),
+                region: "GB".to_owned(),
+                number_type: NumberType::Unknown,
+                valid: true,
+                trunk_prefix: Some("0".to_owned()),
+            })
+        }
+    }
+
+    #[test]
+    fn test_normalizer_wrong_country_code() {
+        let normalizer = PhoneMetadataNormalizer::new(Arc::new(WrongCountryCode));
+        for number in ["+4402079460000", "+1"] {
+            let number: PhoneNumber = number.parse().unwrap();
+            assert!(normalizer.strip_trunk_prefix(&number).is_err(), "{number}");
+            assert_eq!(
+                normalizer.normalize(Atomic::PhoneNumber {
+                    phone_number: number.clone()
+                }),
+                Atomic::PhoneNumber {
+                    phone_number: number
+                }
+            );
+        }
+    }
+}
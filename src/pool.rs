@@ -0,0 +1,147 @@
+//! `pool` module recycles the allocations of subject identifiers, for transmitters that build
+//! and drop subject identifiers at a high rate. A [SubjectIdPool] keeps the member strings and
+//! identifier vectors of recycled subject identifiers, and hands them out cleared, with their
+//! capacity intact, to build the next ones.
+
+use crate::{Aliases, Atomic, Error, PhoneNumber, SubjectId};
+
+/// [SubjectIdPool] is a pool of member string buffers and "identifiers" vectors. It is meant to
+/// be owned by a single transmit loop; use one pool per thread.
+///
+/// ```
+/// use subject_id::{Atomic, SubjectId, SubjectIdPool};
+/// let mut pool = SubjectIdPool::default();
+/// for n in 0..3 {
+///     let email = pool.string(&format!("user{n}@example.com"));
+///     let sub_id = SubjectId::Atomic(Atomic::Email { email });
+///     // ... serialize and send ...
+///     pool.recycle(sub_id);
+/// }
+/// assert_eq!(pool.pooled_strings(), 1);
+/// ```
+#[derive(Debug)]
+pub struct SubjectIdPool {
+    strings: Vec<String>,
+    vecs: Vec<Vec<Atomic>>,
+    max_pooled: usize,
+}
+
+impl SubjectIdPool {
+    /// Default number of buffers of each kind kept by the pool.
+    pub const DEFAULT_MAX_POOLED: usize = 1024;
+
+    /// Pool keeping at most `max_pooled` buffers of each kind; further recycled buffers are
+    /// dropped.
+    pub fn with_max_pooled(max_pooled: usize) -> Self {
+        Self {
+            strings: Vec::new(),
+            vecs: Vec::new(),
+            max_pooled,
+        }
+    }
+
+    /// Copy the value into a pooled string buffer, allocating only if the pool is empty or the
+    /// buffer is too small.
+    pub fn string(&mut self, value: &str) -> String {
+        let mut buf = self.strings.pop().unwrap_or_default();
+        buf.push_str(value);
+        buf
+    }
+
+    /// Parse the phone number into a pooled string buffer, see [PhoneNumber]. The buffer returns
+    /// to the pool if the phone number is invalid.
+    pub fn phone_number(&mut self, value: &str) -> Result<PhoneNumber, Error> {
+        let buf = self.strings.pop().unwrap_or_default();
+        PhoneNumber::parse_into(buf, value).map_err(|(buf, err)| {
+            self.recycle_string(buf);
+            err
+        })
+    }
+
+    /// An empty pooled vector for the "identifiers" member of an "aliases" subject identifier.
+    pub fn identifiers(&mut self) -> Vec<Atomic> {
+        self.vecs.pop().unwrap_or_default()
+    }
+
+    /// Return the buffers of the subject identifier to the pool.
+    pub fn recycle(&mut self, sub_id: SubjectId) {
+        match sub_id {
+            SubjectId::Atomic(id) => self.recycle_atomic(id),
+            SubjectId::Aliases(Aliases { mut identifiers }) => {
+                for id in identifiers.drain(..) {
+                    self.recycle_atomic(id);
+                }
+                if self.vecs.len() < self.max_pooled {
+                    self.vecs.push(identifiers);
+                }
+            }
+        }
+    }
+
+    /// Return the member strings of the atomic identifier to the pool.
+    pub fn recycle_atomic(&mut self, id: Atomic) {
+        match id {
+            Atomic::Account { uri } | Atomic::Uri { uri } => self.recycle_string(uri),
+            Atomic::Email { email } => self.recycle_string(email),
+            Atomic::IssuerSubject { issuer, subject } => {
+                self.recycle_string(issuer);
+                self.recycle_string(subject);
+            }
+            Atomic::Opaque { id } => self.recycle_string(id),
+            Atomic::PhoneNumber { phone_number } => self.recycle_string(phone_number.into_string()),
+            Atomic::Did { url } => self.recycle_string(url),
+        }
+    }
+
+    /// Number of string buffers held by the pool.
+    pub fn pooled_strings(&self) -> usize {
+        self.strings.len()
+    }
+
+    fn recycle_string(&mut self, mut buf: String) {
+        if self.strings.len() < self.max_pooled && buf.capacity() > 0 {
+            buf.clear();
+            self.strings.push(buf);
+        }
+    }
+}
+
+impl Default for SubjectIdPool {
+    fn default() -> Self {
+        Self::with_max_pooled(Self::DEFAULT_MAX_POOLED)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_recycle() {
+        let mut pool = SubjectIdPool::with_max_pooled(2);
+        let mut identifiers = pool.identifiers();
+        identifiers.push(Atomic::IssuerSubject {
+            issuer: pool.string("https://issuer.example.com/"),
+            subject: pool.string("abc"),
+        });
+        identifiers.push(Atomic::PhoneNumber {
+            phone_number: pool.phone_number("12065550100").unwrap(),
+        });
+        pool.recycle(SubjectId::Aliases(Aliases { identifiers }));
+        assert_eq!(pool.pooled_strings(), 2);
+
+        let reused = pool.string("x");
+        assert_eq!(reused, "x");
+        assert!(reused.capacity() >= "abc".len());
+        assert_eq!(pool.pooled_strings(), 1);
+        let identifiers = pool.identifiers();
+        assert!(identifiers.is_empty() && identifiers.capacity() >= 2);
+        let phone = pool.phone_number("+12065550100").unwrap();
+        assert_eq!(phone.as_str(), "+12065550100");
+        pool.recycle_atomic(Atomic::Opaque {
+            id: "abc".to_owned(),
+        });
+        assert!(pool.phone_number("not a number").is_err());
+        assert_eq!(pool.pooled_strings(), 1);
+    }
+}
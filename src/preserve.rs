@@ -0,0 +1,145 @@
+//! `preserve` module parses subject identifiers while preserving their exact input, for receivers
+//! that must forward subject identifiers inside signed payloads untouched. Re-serializing a
+//! [PreservedSubjectId] with `serde_json` reproduces the JSON value of the input byte-for-byte,
+//! including member order, white space between tokens and members unknown to this crate. White
+//! space around the value is not part of it and is not preserved. This module is available
+//! behind the `round-trip` feature.
+
+use crate::{Error, SubjectId};
+use indexmap::IndexMap;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::value::RawValue;
+use serde_json::Value;
+
+/// [PreservedSubjectId] is a [SubjectId] parsed along with its original JSON text and its members
+/// in their original order.
+///
+/// ```
+/// use subject_id::PreservedSubjectId;
+/// let input = r#"{ "id": "1i3j4l", "format": "opaque", "x-note": 1 }"#;
+/// let preserved = PreservedSubjectId::parse(input).unwrap();
+/// assert_eq!(preserved.members().keys().collect::<Vec<_>>(), ["id", "format", "x-note"]);
+/// assert_eq!(serde_json::to_string(&preserved).unwrap(), input);
+/// ```
+#[derive(Clone, Debug)]
+pub struct PreservedSubjectId {
+    raw: Box<RawValue>,
+    members: IndexMap<String, Value>,
+    subject: SubjectId,
+}
+
+impl PreservedSubjectId {
+    /// Parse a subject identifier from JSON text, preserving the text of the JSON value. White
+    /// space before and after the value is dropped.
+    pub fn parse(input: &str) -> Result<Self, Error> {
+        Self::from_raw(RawValue::from_string(input.to_owned())?)
+    }
+
+    fn from_raw(raw: Box<RawValue>) -> Result<Self, Error> {
+        let members = serde_json::from_str(raw.get())?;
+        let subject = serde_json::from_str(raw.get())?;
+        Ok(Self {
+            raw,
+            members,
+            subject,
+        })
+    }
+
+    /// The parsed subject identifier.
+    pub fn subject(&self) -> &SubjectId {
+        &self.subject
+    }
+
+    /// The top-level members of the subject identifier, in their original order.
+    pub fn members(&self) -> &IndexMap<String, Value> {
+        &self.members
+    }
+
+    /// The top-level members that are not part of the parsed subject identifier, in their
+    /// original order.
+    pub fn unknown_members(&self) -> impl Iterator<Item = (&str, &Value)> {
+        let known = serde_json::to_value(&self.subject).expect("subject identifiers serialize");
+        self.members
+            .iter()
+            .filter(move |(name, _)| known.get(name.as_str()).is_none())
+            .map(|(name, value)| (name.as_str(), value))
+    }
+
+    /// The original JSON text of the value, without surrounding white space.
+    pub fn as_str(&self) -> &str {
+        self.raw.get()
+    }
+
+    /// Discard the original text and return the parsed subject identifier.
+    pub fn into_inner(self) -> SubjectId {
+        self.subject
+    }
+}
+
+impl PartialEq for PreservedSubjectId {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl Eq for PreservedSubjectId {}
+
+/// Serializes the original JSON text. Only serializers that support `serde_json` raw values, i.e.
+/// `serde_json` itself, are able to serialize a [PreservedSubjectId].
+impl Serialize for PreservedSubjectId {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.raw.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for PreservedSubjectId {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let raw = Box::<RawValue>::deserialize(deserializer)?;
+        Self::from_raw(raw).map_err(de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Atomic;
+    use serde_json::json;
+
+    #[test]
+    fn test_round_trip() {
+        let input = r#"{
+            "issuer" : "https://issuer.example.com/",
+            "format": "iss_sub", "subject":"145234573", "ext": [1, 2]}"#;
+        let preserved = PreservedSubjectId::parse(input).unwrap();
+        let want = SubjectId::Atomic(Atomic::IssuerSubject {
+            issuer: "https://issuer.example.com/".to_owned(),
+            subject: "145234573".to_owned(),
+        });
+        assert_eq!(preserved.subject(), &want);
+        let unknown: Vec<_> = preserved.unknown_members().collect();
+        assert_eq!(unknown, vec![("ext", &json!([1, 2]))]);
+        assert_eq!(serde_json::to_string(&preserved).unwrap(), input);
+    }
+
+    #[test]
+    fn test_round_trip_surrounding_white_space() {
+        let value = r#"{ "format": "opaque", "id": "1i3j4l" }"#;
+        let preserved = PreservedSubjectId::parse(&format!("\n {value}\t\n")).unwrap();
+        assert_eq!(preserved.as_str(), value);
+        assert_eq!(serde_json::to_string(&preserved).unwrap(), value);
+    }
+
+    #[test]
+    fn test_round_trip_nested() {
+        #[derive(Serialize, Deserialize)]
+        struct Event {
+            subject: PreservedSubjectId,
+        }
+        let input = r#"{"subject":{ "subject":"1", "format" :"iss_sub","issuer":"i" }}"#;
+        let event: Event = serde_json::from_str(input).unwrap();
+        assert_eq!(serde_json::to_string(&event).unwrap(), input);
+    }
+}
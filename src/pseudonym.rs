@@ -0,0 +1,201 @@
+//! `pseudonym` module derives keyed pseudonyms of subject identifiers, so that transmitters can
+//! issue "opaque" subject identifiers that receivers can correlate without learning the
+//! identifier itself. Pseudonyms are the lowercase hex encoded HMAC-SHA256 of the canonical form
+//! of the subject identifier under the default policy, prefixed by the key identifier:
+//! `<key id>:<hex digest>`.
+//!
+//! The key identifier makes key rotation possible: pseudonyms issued under a retired key keep
+//! matching as long as that key is passed to [`SubjectId::matches_any`] alongside the current one.
+
+use crate::{Atomic, CanonicalPolicy, SubjectId};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::fmt::{Debug, Formatter};
+
+/// [PseudonymKey] is an HMAC key with its key identifier.
+///
+/// ```
+/// use subject_id::{Atomic, PseudonymKey, SubjectId};
+/// let old = PseudonymKey::new("2023", b"old secret".to_vec());
+/// let new = PseudonymKey::new("2024", b"new secret".to_vec());
+/// let sub_id = SubjectId::Atomic(Atomic::Email { email: "user@example.com".to_owned() });
+/// let issued = sub_id.pseudonym(&old);
+/// assert!(issued.starts_with("2023:"));
+/// assert!(sub_id.matches_any(&issued, &[new, old]));
+/// ```
+#[derive(Clone)]
+pub struct PseudonymKey {
+    id: String,
+    secret: Vec<u8>,
+}
+
+impl PseudonymKey {
+    /// Key with the given identifier and secret.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the identifier contains ':', which separates it from the digest in pseudonyms.
+    pub fn new(id: impl Into<String>, secret: Vec<u8>) -> Self {
+        let id = id.into();
+        assert!(!id.contains(':'), "key identifier {id:?} contains ':'");
+        Self { id, secret }
+    }
+
+    /// The key identifier.
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// HMAC of the canonical form of the subject identifier, after the label which separates
+    /// the derivations made with the same key.
+    fn mac(&self, label: &[u8], sub_id: &SubjectId) -> Hmac<Sha256> {
+        let canonical = sub_id.canonicalize(&CanonicalPolicy::default());
+        let json = serde_json::to_vec(&canonical).expect("subject identifiers always serialize");
+        let mut mac =
+            Hmac::<Sha256>::new_from_slice(&self.secret).expect("HMAC accepts keys of any length");
+        mac.update(label);
+        mac.update(&json);
+        mac
+    }
+}
+
+/// The secret is never printed.
+impl Debug for PseudonymKey {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PseudonymKey")
+            .field("id", &self.id)
+            .finish_non_exhaustive()
+    }
+}
+
+impl SubjectId {
+    /// Keyed pseudonym of the [SubjectId] of the form `<key id>:<hex digest>`. Identifiers with
+    /// the same canonical form have the same pseudonym under the same key.
+    pub fn pseudonym(&self, key: &PseudonymKey) -> String {
+        format!("{}:{}", key.id, to_hex(key.mac(PSEUDONYM_LABEL, self)))
+    }
+
+    /// Keyed fingerprint of the [SubjectId]: the lowercase hex encoded HMAC-SHA256 of its
+    /// canonical form under the default policy. The fingerprint allows correlating subjects in
+    /// logs and exports; without the key it cannot be recomputed from guessed identifiers. It is
+    /// derived separately from the pseudonym, so that internal logs cannot be linked with the
+    /// pseudonyms shared with third parties.
+    ///
+    /// ```
+    /// use subject_id::{Atomic, PseudonymKey, SubjectId};
+    /// let key = PseudonymKey::new("logs", b"secret".to_vec());
+    /// let a = SubjectId::Atomic(Atomic::Email { email: "user@example.com".to_owned() });
+    /// let b = SubjectId::Atomic(Atomic::Email { email: "user@EXAMPLE.com".to_owned() });
+    /// assert_eq!(a.fingerprint(&key).len(), 64);
+    /// assert_eq!(a.fingerprint(&key), b.fingerprint(&key));
+    /// ```
+    pub fn fingerprint(&self, key: &PseudonymKey) -> String {
+        to_hex(key.mac(FINGERPRINT_LABEL, self))
+    }
+
+    /// The pseudonym of the [SubjectId] as an "opaque" subject identifier.
+    pub fn to_pseudonymous(&self, key: &PseudonymKey) -> Self {
+        Self::Atomic(Atomic::Opaque {
+            id: self.pseudonym(key),
+        })
+    }
+
+    /// Report whether the pseudonym was derived from the [SubjectId] under one of the keys. Only
+    /// the keys whose identifier matches the one of the pseudonym are tried; digests are compared
+    /// in constant time.
+    pub fn matches_any(&self, pseudonym: &str, keys: &[PseudonymKey]) -> bool {
+        let Some((key_id, digest)) = pseudonym.rsplit_once(':') else {
+            return false;
+        };
+        let Some(digest) = decode_hex(digest) else {
+            return false;
+        };
+        keys.iter()
+            .filter(|key| key.id == key_id)
+            .any(|key| key.mac(PSEUDONYM_LABEL, self).verify_slice(&digest).is_ok())
+    }
+}
+
+/// Label of pseudonyms, empty so that pseudonyms stay those issued before fingerprints existed.
+const PSEUDONYM_LABEL: &[u8] = b"";
+
+/// Label of fingerprints.
+const FINGERPRINT_LABEL: &[u8] = b"fingerprint\0";
+
+fn to_hex(mac: Hmac<Sha256>) -> String {
+    mac.finalize()
+        .into_bytes()
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
+
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) || !hex.is_ascii() {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use sha2::Digest;
+
+    #[test]
+    fn test_matches_any() {
+        let old = PseudonymKey::new("k1", b"first".to_vec());
+        let new = PseudonymKey::new("k2", b"second".to_vec());
+        let email = |email: &str| {
+            SubjectId::Atomic(Atomic::Email {
+                email: email.to_owned(),
+            })
+        };
+        let sub_id = email("user@example.com");
+        let issued = sub_id.pseudonym(&old);
+        assert_eq!(issued, email("user@EXAMPLE.com").pseudonym(&old));
+        assert_ne!(issued, sub_id.pseudonym(&new));
+
+        let keys = [new, old];
+        assert!(sub_id.matches_any(&issued, &keys));
+        assert!(!sub_id.matches_any(&issued, &keys[..1]));
+        assert!(!email("other@example.com").matches_any(&issued, &keys));
+        let forged = issued.replacen("k1:", "k2:", 1);
+        assert!(!sub_id.matches_any(&forged, &keys));
+        assert!(!sub_id.matches_any("k1:zz", &keys));
+    }
+
+    #[test]
+    fn test_fingerprint() {
+        let key = PseudonymKey::new("k1", b"first".to_vec());
+        let sub_id = SubjectId::Atomic(Atomic::Email {
+            email: "user@EXAMPLE.com".to_owned(),
+        });
+        let canonical = sub_id.canonicalize(&CanonicalPolicy::default());
+        let json = serde_json::to_vec(&canonical).unwrap();
+        let unkeyed: String = Sha256::digest(json)
+            .iter()
+            .map(|b| format!("{b:02x}"))
+            .collect();
+        let fingerprint = sub_id.fingerprint(&key);
+        assert_ne!(fingerprint, unkeyed);
+        assert_ne!(format!("k1:{fingerprint}"), sub_id.pseudonym(&key));
+        let other = PseudonymKey::new("k1", b"second".to_vec());
+        assert_ne!(fingerprint, sub_id.fingerprint(&other));
+    }
+
+    #[test]
+    #[should_panic(expected = "contains ':'")]
+    fn test_key_id_with_colon() {
+        PseudonymKey::new("k:1", b"first".to_vec());
+    }
+
+    #[test]
+    fn test_debug_hides_secret() {
+        let key = PseudonymKey::new("k1", b"hunter2".to_vec());
+        assert!(!format!("{key:?}").contains("hunter2"));
+    }
+}
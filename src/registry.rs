@@ -0,0 +1,136 @@
+//! `registry` module compares the formats supported by this crate with the IANA "Security Event
+//! Identifier Formats" registry, to report formats registered since this version was released.
+//!
+//! Fetching is left to the caller: download the CSV export at [REGISTRY_CSV_URL] with the HTTP
+//! client of the application and pass its content to [diff].
+//!
+//! ```
+//! use subject_id::registry;
+//! let csv = "Format Name,Description,Change Controller,Reference\r\n\
+//!            email,Email address,IETF,[RFC9493]\r\n\
+//!            \"ip_address\",\"IP address, v4 or v6\",IETF,[RFC-to-be]\r\n";
+//! let diff = registry::diff(csv);
+//! assert_eq!(diff.unsupported, vec!["ip_address"]);
+//! ```
+
+use crate::SubjectId;
+
+/// Location of the CSV export of the IANA "Security Event Identifier Formats" registry.
+pub const REGISTRY_CSV_URL: &str =
+    "https://www.iana.org/assignments/secevent/identifier-formats.csv";
+
+/// The Identifier Formats supported by this crate.
+pub const SUPPORTED_FORMATS: &[&str] = &[
+    SubjectId::FORMAT_ACCOUNT,
+    SubjectId::FORMAT_ALIASES,
+    SubjectId::FORMAT_DID,
+    SubjectId::FORMAT_EMAIL,
+    SubjectId::FORMAT_ISSUER_SUBJECT,
+    SubjectId::FORMAT_OPAQUE,
+    SubjectId::FORMAT_PHONE_NUMBER,
+    SubjectId::FORMAT_URI,
+];
+
+/// [RegistryDiff] is the difference between the registry and [SUPPORTED_FORMATS].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RegistryDiff {
+    /// Registered formats that this crate does not support, in registry order.
+    pub unsupported: Vec<String>,
+    /// Supported formats missing from the registry, e.g. formats still in draft.
+    pub unregistered: Vec<&'static str>,
+}
+
+impl RegistryDiff {
+    /// Report whether the crate supports exactly the registered formats.
+    pub fn is_empty(&self) -> bool {
+        self.unsupported.is_empty() && self.unregistered.is_empty()
+    }
+}
+
+/// Compare the registry, given as the content of its CSV export, with [SUPPORTED_FORMATS].
+pub fn diff(csv: &str) -> RegistryDiff {
+    let registered = registered_formats(csv);
+    let unsupported = registered
+        .iter()
+        .filter(|name| !SUPPORTED_FORMATS.contains(&name.as_str()))
+        .cloned()
+        .collect();
+    let unregistered = SUPPORTED_FORMATS
+        .iter()
+        .copied()
+        .filter(|name| !registered.iter().any(|r| r == name))
+        .collect();
+    RegistryDiff {
+        unsupported,
+        unregistered,
+    }
+}
+
+/// The format names of the registry, given as the content of its CSV export: the first field of
+/// every record after the header.
+pub fn registered_formats(csv: &str) -> Vec<String> {
+    records(csv)
+        .into_iter()
+        .skip(1)
+        .filter_map(|record| record.into_iter().next())
+        .map(|name| name.trim().to_owned())
+        .filter(|name| !name.is_empty())
+        .collect()
+}
+
+/// Split RFC 4180 CSV into records of fields. Quoted fields may contain separators, line breaks
+/// and doubled quotes.
+fn records(csv: &str) -> Vec<Vec<String>> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = csv.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (quoted, c) {
+            (true, '"') if chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            (true, '"') => quoted = false,
+            (true, c) => field.push(c),
+            (false, '"') => quoted = true,
+            (false, ',') => record.push(std::mem::take(&mut field)),
+            (false, '\r') => {}
+            (false, '\n') => {
+                record.push(std::mem::take(&mut field));
+                records.push(std::mem::take(&mut record));
+            }
+            (false, c) => field.push(c),
+        }
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+    records
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_diff() {
+        let csv = "Format Name,Description,Change Controller,Reference\n\
+                   account,\"Account, \"\"acct\"\" URI\",IETF,[RFC9493]\n\
+                   email,\"Email\naddress\",IETF,[RFC9493]\n\
+                   ip_address,IP address,IETF,[draft]";
+        assert_eq!(
+            registered_formats(csv),
+            vec!["account", "email", "ip_address"]
+        );
+        let got = diff(csv);
+        assert_eq!(got.unsupported, vec!["ip_address"]);
+        assert_eq!(
+            got.unregistered,
+            vec!["aliases", "did", "iss_sub", "opaque", "phone_number", "uri"]
+        );
+        assert!(!got.is_empty());
+    }
+}
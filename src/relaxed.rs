@@ -0,0 +1,57 @@
+//! `relaxed` module parses subject identifiers from [`JSON5`], a relaxed superset of JSON allowing
+//! comments, trailing commas, single quotes and unquoted member names. It is meant for
+//! developer-facing input such as configuration files of watched subjects; subject identifiers
+//! on the wire are strict JSON and are parsed with `serde_json`. This module is available behind
+//! the `json5` feature.
+//!
+//! [`JSON5`]: https://spec.json5.org/
+
+use crate::{Error, SubjectId};
+
+impl SubjectId {
+    /// Parse a [SubjectId] from JSON5 text. To read a list of subject identifiers, deserialize a
+    /// `Vec<SubjectId>` with [`json5::from_str`].
+    ///
+    /// ```
+    /// use subject_id::SubjectId;
+    /// let input = "{
+    ///     // the on-call account
+    ///     format: 'email',
+    ///     email: 'oncall@example.com',
+    /// }";
+    /// let sub_id = SubjectId::from_json5(input).unwrap();
+    /// assert_eq!(sub_id.format(), SubjectId::FORMAT_EMAIL);
+    /// ```
+    pub fn from_json5(input: &str) -> Result<Self, Error> {
+        Ok(json5::from_str(input)?)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{Aliases, Atomic};
+
+    #[test]
+    fn test_from_json5() {
+        let input = r#"/* watched subjects */ {
+            format: "aliases",
+            identifiers: [
+                {format: 'phone_number', phone_number: '+12065550100'},
+                {format: 'iss_sub', issuer: 'https://issuer.example.com/', subject: "abc",},
+            ],
+        }"#;
+        let want = SubjectId::Aliases(Aliases::from(vec![
+            Atomic::PhoneNumber {
+                phone_number: "+12065550100".parse().unwrap(),
+            },
+            Atomic::IssuerSubject {
+                issuer: "https://issuer.example.com/".to_owned(),
+                subject: "abc".to_owned(),
+            },
+        ]));
+        assert_eq!(SubjectId::from_json5(input).unwrap(), want);
+        let got = SubjectId::from_json5("{format: 'opaque'}");
+        assert!(matches!(got, Err(Error::Json5(..))));
+    }
+}
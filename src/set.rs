@@ -0,0 +1,137 @@
+//! `set` module extracts subject identifiers from the body (claims set) of a Security Event Token
+//! as defined in [`RFC8417`]. The subject of an event is given either by the top-level "sub_id"
+//! claim, shared by every event of the token, or by a "subject" member of the event payload.
+//!
+//! [`RFC8417`]: https://www.rfc-editor.org/info/rfc8417
+
+use crate::{ComplexSubject, SubjectId};
+use serde::Deserialize;
+use serde_json::Value;
+
+/// [EventSubject] is the subject of a security event: a subject identifier, or a complex subject
+/// whose "format" member is "complex".
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum EventSubject {
+    /// The subject is identified with a subject identifier.
+    SubjectId(SubjectId),
+    /// The subject is a complex subject.
+    Complex(Box<ComplexSubject>),
+}
+
+impl EventSubject {
+    /// Parse the subject of an event, telling complex subjects apart by their "format" member.
+    pub fn from_value(value: &Value) -> Result<Self, crate::Error> {
+        match value.get("format").and_then(Value::as_str) {
+            Some("complex") => Ok(Self::Complex(Box::new(ComplexSubject::deserialize(value)?))),
+            _ => Ok(Self::SubjectId(SubjectId::deserialize(value)?)),
+        }
+    }
+}
+
+/// Extract the subject of each event of the "events" claim, paired with the event type URI and
+/// ordered by event type URI. The "subject" member of the event payload takes precedence
+/// over the top-level "sub_id" claim. Events without a subject are skipped; events whose subject
+/// is neither a subject identifier nor a complex subject are reported with the parse error, so
+/// that a malformed subject is not mistaken for an absent one.
+///
+/// ```
+/// use serde_json::json;
+/// use subject_id::set;
+/// let body = json!({
+///     "iss": "https://idp.example.com/",
+///     "sub_id": { "format": "email", "email": "user@example.com" },
+///     "events": {
+///         "https://schemas.openid.net/secevent/caep/event-type/session-revoked": {},
+///     },
+/// });
+/// let subjects = set::extract_subjects(&body);
+/// assert_eq!(subjects.len(), 1);
+/// assert!(subjects[0].1.is_ok());
+/// ```
+pub fn extract_subjects(body: &Value) -> Vec<(String, Result<EventSubject, crate::Error>)> {
+    let Some(events) = body.get("events").and_then(Value::as_object) else {
+        return Vec::new();
+    };
+    let sub_id = body.get("sub_id");
+    events
+        .iter()
+        .filter_map(|(event_type, payload)| {
+            let subject = payload.get("subject").or(sub_id)?;
+            Some((event_type.clone(), EventSubject::from_value(subject)))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Atomic;
+    use serde_json::json;
+
+    #[test]
+    fn test_extract_subjects() {
+        let body = json!({
+            "iss": "https://idp.example.com/",
+            "sub_id": { "format": "opaque", "id": "1i3j4l" },
+            "events": {
+                "urn:example:a": {},
+                "urn:example:b": {
+                    "subject": { "format": "email", "email": "user@example.com" },
+                },
+                "urn:example:c": {
+                    "subject": {
+                        "format": "complex",
+                        "user": { "format": "opaque", "id": "x" },
+                    },
+                },
+                "urn:example:d": {
+                    "subject": { "user": { "format": "opaque", "id": "x" } },
+                },
+            },
+        });
+        let got: Vec<_> = extract_subjects(&body)
+            .into_iter()
+            .map(|(event_type, subject)| (event_type, subject.ok()))
+            .collect();
+        let want = vec![
+            (
+                "urn:example:a".to_owned(),
+                Some(EventSubject::SubjectId(SubjectId::Atomic(Atomic::Opaque {
+                    id: "1i3j4l".to_owned(),
+                }))),
+            ),
+            (
+                "urn:example:b".to_owned(),
+                Some(EventSubject::SubjectId(SubjectId::Atomic(Atomic::Email {
+                    email: "user@example.com".to_owned(),
+                }))),
+            ),
+            (
+                "urn:example:c".to_owned(),
+                Some(EventSubject::Complex(Box::new(ComplexSubject::user(
+                    Atomic::Opaque { id: "x".to_owned() },
+                )))),
+            ),
+            ("urn:example:d".to_owned(), None),
+        ];
+        assert_eq!(got, want);
+        assert!(extract_subjects(&json!({ "iss": "x" })).is_empty());
+    }
+
+    #[test]
+    fn test_extract_subjects_malformed() {
+        let body = json!({
+            "sub_id": { "format": "email" },
+            "events": {
+                "urn:example:a": {},
+                "urn:example:b": { "subject": { "format": "complex" } },
+                "urn:example:c": { "subject": { "format": "opaque", "id": "x" } },
+            },
+        });
+        let got = extract_subjects(&body);
+        assert_eq!(got.len(), 3);
+        assert!(got[0].1.is_err());
+        assert!(got[1].1.is_err());
+        assert!(got[2].1.is_ok());
+    }
+}
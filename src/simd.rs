@@ -0,0 +1,71 @@
+//! `simd` module integrates subject identifiers with [`simd-json`], so that receivers which
+//! already parse Security Event Tokens with simd-json need not convert back to `serde_json`.
+//! This module is available behind the `simd-json` feature.
+//!
+//! [`simd-json`]: https://docs.rs/simd-json
+
+use crate::{Error, SubjectId};
+use simd_json::Tape;
+
+impl SubjectId {
+    /// Parse a [SubjectId] from a mutable JSON byte buffer using simd-json. The buffer is used as
+    /// scratch space by the parser and its contents are unspecified afterwards.
+    ///
+    /// ```
+    /// use subject_id::SubjectId;
+    /// let mut input = br#"{"format":"opaque","id":"1i3j4l"}"#.to_vec();
+    /// let sub_id = SubjectId::from_simd_json(&mut input).unwrap();
+    /// assert_eq!(sub_id.format(), SubjectId::FORMAT_OPAQUE);
+    /// ```
+    pub fn from_simd_json(input: &mut [u8]) -> Result<Self, Error> {
+        Ok(simd_json::serde::from_slice(input)?)
+    }
+
+    /// Deserialize a [SubjectId] from an already parsed simd-json [Tape]. This is useful when the
+    /// tape of a whole SET has been obtained with [`simd_json::to_tape`] and the subject identifier
+    /// is deserialized from it without parsing the input again.
+    ///
+    /// ```
+    /// use subject_id::SubjectId;
+    /// let mut input = br#"{"format":"email","email":"user@example.com"}"#.to_vec();
+    /// let tape = simd_json::to_tape(&mut input).unwrap();
+    /// let sub_id = SubjectId::from_simd_tape(tape).unwrap();
+    /// assert_eq!(sub_id.format(), SubjectId::FORMAT_EMAIL);
+    /// ```
+    pub fn from_simd_tape(tape: Tape<'_>) -> Result<Self, Error> {
+        Ok(tape.deserialize()?)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{Aliases, Atomic, SubjectId};
+
+    #[test]
+    fn test_from_simd_json() {
+        let mut input = br#"{"format":"aliases","identifiers":[
+            {"format":"phone_number","phone_number":"+12065550100"},
+            {"format":"iss_sub","issuer":"https://issuer.example.com/","subject":"145234573"}
+        ]}"#
+        .to_vec();
+        let got = SubjectId::from_simd_json(&mut input).unwrap();
+        let want = SubjectId::Aliases(Aliases {
+            identifiers: vec![
+                Atomic::PhoneNumber {
+                    phone_number: "+12065550100".parse().unwrap(),
+                },
+                Atomic::IssuerSubject {
+                    issuer: "https://issuer.example.com/".to_owned(),
+                    subject: "145234573".to_owned(),
+                },
+            ],
+        });
+        assert_eq!(got, want);
+    }
+
+    #[test]
+    fn test_from_simd_json_invalid() {
+        let mut input = br#"{"format":"phone_number","phone_number":"abc"}"#.to_vec();
+        assert!(SubjectId::from_simd_json(&mut input).is_err());
+    }
+}
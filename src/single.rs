@@ -7,7 +7,8 @@ use serde::{Deserialize, Serialize};
 
 /// Atomic defines atomic subject identifier formats. They are 'atomic' because (unlike aliases)
 /// these are not composed of other subject identifiers themselves.
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
 #[serde(rename_all = "snake_case")]
 #[serde(tag = "format")]
 pub enum Atomic {
@@ -142,6 +143,7 @@ pub enum Atomic {
         /// The "phone_number" member is REQUIRED and MUST NOT be null or empty.
         ///
         /// [`E164`]: https://www.itu.int/rec/T-REC-E.164-201011-I/en
+        #[cfg_attr(feature = "ts", ts(type = "string"))]
         phone_number: PhoneNumber,
     },
     /// The Decentralized Identifier Format identifies a subject using a Decentralized Identifier
@@ -205,6 +207,83 @@ impl Atomic {
             Atomic::Uri { .. } => SubjectId::FORMAT_URI,
         }
     }
+
+    /// Members of the atomic identifier other than "format", paired with their member names, in
+    /// the order they are defined by the Identifier Format.
+    ///
+    /// ```
+    /// use subject_id::Atomic;
+    /// let sub_id = Atomic::Email { email: "user@example.com".to_owned() };
+    /// assert_eq!(sub_id.members(), vec![("email", "user@example.com")]);
+    /// ```
+    pub fn members(&self) -> Vec<(&'static str, &str)> {
+        match self {
+            Atomic::Account { uri } => vec![("uri", uri)],
+            Atomic::Email { email } => vec![("email", email)],
+            Atomic::IssuerSubject { issuer, subject } => {
+                vec![("issuer", issuer), ("subject", subject)]
+            }
+            Atomic::Opaque { id } => vec![("id", id)],
+            Atomic::PhoneNumber { phone_number } => vec![("phone_number", phone_number.as_str())],
+            Atomic::Did { url } => vec![("url", url)],
+            Atomic::Uri { uri } => vec![("uri", uri)],
+        }
+    }
+
+    /// The member value that identifies the subject: the "sub" member of an "iss_sub" identifier,
+    /// or the only member of any other atomic identifier format.
+    ///
+    /// ```
+    /// use subject_id::Atomic;
+    /// let sub_id = Atomic::Did { url: "did:example:123456".to_owned() };
+    /// assert_eq!(sub_id.value(), "did:example:123456");
+    /// ```
+    pub fn value(&self) -> &str {
+        match self {
+            Atomic::Account { uri } | Atomic::Uri { uri } => uri,
+            Atomic::Email { email } => email,
+            Atomic::IssuerSubject { subject, .. } => subject,
+            Atomic::Opaque { id } => id,
+            Atomic::PhoneNumber { phone_number } => phone_number.as_str(),
+            Atomic::Did { url } => url,
+        }
+    }
+
+    /// Domain the atomic identifier is scoped to, if the Identifier Format carries one: the domain
+    /// of an email address or an "acct" URI, or the host of the issuer of an "iss_sub" identifier
+    /// or of a hierarchical URI.
+    ///
+    /// ```
+    /// use subject_id::Atomic;
+    /// let sub_id = Atomic::IssuerSubject {
+    ///     issuer: "https://issuer.example.com/".to_owned(),
+    ///     subject: "145234573".to_owned(),
+    /// };
+    /// assert_eq!(sub_id.domain(), Some("issuer.example.com"));
+    /// ```
+    pub fn domain(&self) -> Option<&str> {
+        match self {
+            Atomic::Email { email } => email.rsplit_once('@').map(|(_, domain)| domain),
+            Atomic::Account { uri } => uri.rsplit_once('@').map(|(_, domain)| domain),
+            Atomic::IssuerSubject { issuer, .. } => uri_host(issuer),
+            Atomic::Uri { uri } => uri_host(uri),
+            Atomic::Opaque { .. } | Atomic::PhoneNumber { .. } | Atomic::Did { .. } => None,
+        }
+    }
+}
+
+/// Host of the authority component of a hierarchical URI, if any.
+pub(crate) fn uri_host(uri: &str) -> Option<&str> {
+    let (_, rest) = uri.split_once("://")?;
+    let authority = rest.split(['/', '?', '#']).next()?;
+    let host = authority
+        .rsplit_once('@')
+        .map_or(authority, |(_, host)| host);
+    let host = match host.find(']') {
+        Some(end) if host.starts_with('[') => &host[..=end],
+        _ => host.split(':').next().unwrap_or(host),
+    };
+    (!host.is_empty()).then_some(host)
 }
 
 #[cfg(test)]
@@ -212,6 +291,7 @@ mod test {
     use super::*;
 
     #[test]
+    #[allow(clippy::useless_vec)]
     fn test_format() {
         let cases = vec![
             Atomic::Account {
@@ -250,4 +330,26 @@ mod test {
             assert_eq!(got, want, "format values do not match");
         }
     }
+
+    #[test]
+    fn test_domain() {
+        let cases = [
+            (
+                "https://issuer.example.com:8443/path",
+                Some("issuer.example.com"),
+            ),
+            ("https://user@[::1]:8443/", Some("[::1]")),
+            ("urn:ietf:rfc:2648", None),
+        ];
+        for (uri, want) in cases {
+            let sub_id = Atomic::Uri {
+                uri: uri.to_owned(),
+            };
+            assert_eq!(sub_id.domain(), want, "domain of {uri}");
+        }
+        let sub_id = Atomic::Email {
+            email: "someone@example.com".to_owned(),
+        };
+        assert_eq!(sub_id.domain(), Some("example.com"));
+    }
 }
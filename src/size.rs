@@ -0,0 +1,108 @@
+//! `size` module computes the length of the compact JSON serialization of subject identifiers
+//! without serializing them, so that transmitters can size buffers and enforce payload limits
+//! up front.
+
+use crate::{Aliases, Atomic, SubjectId};
+
+impl SubjectId {
+    /// Upper bound on the length in bytes of the compact JSON serialization of the [SubjectId],
+    /// as produced by [`serde_json::to_vec`]. The bound is exact, since `serde_json` only escapes
+    /// quotes, backslashes and control characters.
+    ///
+    /// ```
+    /// use subject_id::{Atomic, SubjectId};
+    /// let sub_id = SubjectId::Atomic(Atomic::Opaque { id: "1i3j4l".to_owned() });
+    /// let json = serde_json::to_vec(&sub_id).unwrap();
+    /// assert!(json.len() <= sub_id.serialized_len_hint());
+    /// ```
+    pub fn serialized_len_hint(&self) -> usize {
+        match self {
+            Self::Atomic(id) => id.serialized_len_hint(),
+            Self::Aliases(aliases) => aliases.serialized_len_hint(),
+        }
+    }
+}
+
+impl Aliases {
+    /// Upper bound on the length of the compact JSON serialization, see
+    /// [`SubjectId::serialized_len_hint`].
+    pub fn serialized_len_hint(&self) -> usize {
+        let identifiers: usize = self
+            .identifiers
+            .iter()
+            .map(Atomic::serialized_len_hint)
+            .sum();
+        let commas = self.identifiers.len().saturating_sub(1);
+        // {"format":"aliases","identifiers":[...]}
+        2 + member_len("format", SubjectId::FORMAT_ALIASES)
+            + 1
+            + string_len("identifiers")
+            + 1
+            + 2
+            + identifiers
+            + commas
+    }
+}
+
+impl Atomic {
+    /// Upper bound on the length of the compact JSON serialization, see
+    /// [`SubjectId::serialized_len_hint`].
+    pub fn serialized_len_hint(&self) -> usize {
+        let members = self.members();
+        let members_len: usize = members
+            .iter()
+            .map(|(name, value)| 1 + member_len(name, value))
+            .sum();
+        // {"format":"...",...}
+        2 + member_len("format", self.format()) + members_len
+    }
+}
+
+/// Length of `"name":"value"`.
+fn member_len(name: &str, value: &str) -> usize {
+    string_len(name) + 1 + string_len(value)
+}
+
+/// Length of the string once quoted and escaped.
+fn string_len(s: &str) -> usize {
+    let escaped: usize = s
+        .bytes()
+        .map(|b| match b {
+            b'"' | b'\\' | b'\x08' | b'\x0C' | b'\n' | b'\r' | b'\t' => 2,
+            0x00..=0x1F => 6,
+            _ => 1,
+        })
+        .sum();
+    escaped + 2
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_serialized_len_hint() {
+        let cases = [
+            SubjectId::Atomic(Atomic::Email {
+                email: "\"john\\doe\"@example.com".to_owned(),
+            }),
+            SubjectId::Atomic(Atomic::IssuerSubject {
+                issuer: "https://issuer.example.com/".to_owned(),
+                subject: "tab\there\u{1}ünï".to_owned(),
+            }),
+            SubjectId::Aliases(Aliases::from(vec![
+                Atomic::PhoneNumber {
+                    phone_number: "+12065550100".parse().unwrap(),
+                },
+                Atomic::Did {
+                    url: "did:example:123456".to_owned(),
+                },
+            ])),
+            SubjectId::Aliases(Aliases::from(vec![])),
+        ];
+        for sub_id in cases {
+            let json = serde_json::to_vec(&sub_id).unwrap();
+            assert_eq!(sub_id.serialized_len_hint(), json.len(), "{sub_id:?}");
+        }
+    }
+}
@@ -0,0 +1,127 @@
+//! `ssf` module defines the payloads of the stream management events of the Shared Signals
+//! Framework ([`SSF`]). These events are about an event stream rather than a user: the "sub_id"
+//! claim of their Security Event Token is an "opaque" subject identifier holding the stream ID.
+//!
+//! [`SSF`]: https://openid.net/specs/openid-sharedsignals-framework-1_0.html
+
+use crate::{Atomic, Error, SubjectId};
+use serde::{Deserialize, Serialize};
+
+/// Subject identifier of an event stream, to be used as the "sub_id" claim of stream management
+/// events.
+///
+/// ```
+/// use subject_id::ssf;
+/// let sub_id = ssf::stream_subject("f67e39a0a4d34d56b3aa1bc4cff0069f");
+/// assert_eq!(ssf::stream_id(&sub_id), Some("f67e39a0a4d34d56b3aa1bc4cff0069f"));
+/// ```
+pub fn stream_subject(stream_id: impl Into<String>) -> SubjectId {
+    SubjectId::Atomic(Atomic::Opaque {
+        id: stream_id.into(),
+    })
+}
+
+/// Stream ID held by the "sub_id" claim of a stream management event, if it is an "opaque"
+/// subject identifier.
+pub fn stream_id(sub_id: &SubjectId) -> Option<&str> {
+    match sub_id {
+        SubjectId::Atomic(Atomic::Opaque { id }) => Some(id),
+        _ => None,
+    }
+}
+
+/// The verification event is sent by a transmitter in response to a verification request of the
+/// receiver, echoing the "state" the receiver supplied in its request.
+///
+/// ```
+/// use subject_id::ssf::VerificationEvent;
+/// let event: VerificationEvent = serde_json::from_str(r#"{"state":"VGhpcyBpcyBhbiBleGFt"}"#)
+///     .unwrap();
+/// assert!(event.verify_state(Some("VGhpcyBpcyBhbiBleGFt")).is_ok());
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VerificationEvent {
+    /// The "state" value supplied by the receiver in the verification request, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub state: Option<String>,
+}
+
+impl VerificationEvent {
+    /// Event type URI of the verification event.
+    pub const EVENT_TYPE: &'static str =
+        "https://schemas.openid.net/secevent/ssf/event-type/verification";
+
+    /// Check that the event echoes the "state" the receiver supplied in its verification request.
+    /// A verification request without "state" expects an event without "state".
+    pub fn verify_state(&self, expected: Option<&str>) -> Result<(), Error> {
+        if self.state.as_deref() != expected {
+            return Err(Error::StateMismatch);
+        }
+        Ok(())
+    }
+}
+
+/// The stream updated event is sent by a transmitter when the status of a stream changes.
+///
+/// ```
+/// use subject_id::ssf::{StreamStatus, StreamUpdatedEvent};
+/// let event: StreamUpdatedEvent = serde_json::from_str(r#"{"status":"paused"}"#).unwrap();
+/// assert_eq!(event.status, StreamStatus::Paused);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StreamUpdatedEvent {
+    /// The new status of the stream.
+    pub status: StreamStatus,
+    /// Explanation of why the status changed, for display to an administrator.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+impl StreamUpdatedEvent {
+    /// Event type URI of the stream updated event.
+    pub const EVENT_TYPE: &'static str =
+        "https://schemas.openid.net/secevent/ssf/event-type/stream-updated";
+}
+
+/// [StreamStatus] is the status of an event stream.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StreamStatus {
+    /// The transmitter transmits events over the stream.
+    Enabled,
+    /// The transmitter holds events and transmits them once the stream is enabled again.
+    Paused,
+    /// The transmitter neither transmits nor holds events.
+    Disabled,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_verification_state() {
+        let event = VerificationEvent {
+            state: Some("abc".to_owned()),
+        };
+        assert!(event.verify_state(Some("abc")).is_ok());
+        assert!(matches!(
+            event.verify_state(Some("xyz")),
+            Err(Error::StateMismatch)
+        ));
+        assert!(event.verify_state(None).is_err());
+        let event = VerificationEvent::default();
+        assert_eq!(serde_json::to_value(&event).unwrap(), json!({}));
+        assert!(event.verify_state(None).is_ok());
+    }
+
+    #[test]
+    fn test_stream_updated() {
+        let json = json!({ "status": "disabled", "reason": "receiver unreachable" });
+        let event: StreamUpdatedEvent = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(event.status, StreamStatus::Disabled);
+        assert_eq!(serde_json::to_value(&event).unwrap(), json);
+        assert_eq!(stream_id(&stream_subject("s-1")), Some("s-1"));
+    }
+}
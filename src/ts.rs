@@ -0,0 +1,76 @@
+//! `ts` module provides [`ts-rs`] TypeScript bindings for the subject identifier types, so that
+//! frontends get discriminated-union types generated from the Rust source. [SubjectId] and
+//! [Atomic] derive the bindings; [Aliases] implements them by hand, because the derived bindings
+//! cannot keep the "aliases" format name apart from the `Aliases` type name. This module is
+//! available behind the `ts` feature.
+//!
+//! [`ts-rs`]: https://docs.rs/ts-rs
+//!
+//! ```
+//! use subject_id::SubjectId;
+//! use ts_rs::TS;
+//! assert_eq!(SubjectId::decl(), "type SubjectId = Atomic | Aliases;");
+//! ```
+
+use crate::{Aliases, Atomic};
+use std::path::PathBuf;
+use ts_rs::{TypeVisitor, TS};
+
+impl TS for Aliases {
+    type WithoutGenerics = Self;
+    type OptionInnerType = Self;
+
+    fn docs() -> Option<String> {
+        Some("The Aliases Identifier Format, identified by the name \"aliases\".".to_owned())
+    }
+
+    fn decl() -> String {
+        format!("type {} = {};", Self::name(), Self::inline())
+    }
+
+    fn decl_concrete() -> String {
+        Self::decl()
+    }
+
+    fn name() -> String {
+        "Aliases".to_owned()
+    }
+
+    fn inline() -> String {
+        format!(
+            r#"{{ "format": "aliases", identifiers: Array<{}>, }}"#,
+            Atomic::name()
+        )
+    }
+
+    fn inline_flattened() -> String {
+        Self::inline()
+    }
+
+    fn visit_dependencies(v: &mut impl TypeVisitor)
+    where
+        Self: 'static,
+    {
+        v.visit::<Atomic>();
+    }
+
+    fn output_path() -> Option<PathBuf> {
+        Some(PathBuf::from("Aliases.ts"))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_ts_bindings() {
+        assert_eq!(
+            Aliases::decl(),
+            r#"type Aliases = { "format": "aliases", identifiers: Array<Atomic>, };"#
+        );
+        let atomic = Atomic::decl();
+        assert!(atomic.contains(r#"{ "format": "iss_sub", "#));
+        assert!(atomic.contains("phone_number: string"));
+    }
+}
@@ -0,0 +1,450 @@
+//! `validate` module checks subject identifiers against the rules of their Identifier Format.
+//! Deserialization already enforces the structure of subject identifiers, but the member values
+//! of identifiers constructed in code (or received from lenient peers) may still be
+//! non-conformant, e.g. empty or of the wrong syntax.
+//!
+//! Applications can tighten the rules with a [ValidationPolicy], e.g. to require that "opaque"
+//! identifiers are UUIDs, since any non-empty string is a conformant "opaque" identifier.
+
+use crate::{Aliases, Atomic, EmailAddress, Error, PhonePolicy, SubjectId};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{ser, Serialize, Serializer};
+use std::borrow::Borrow;
+
+/// Regular expression that defines the structure of a DID URL: the "did" scheme, a method name
+/// and a method-specific identifier.
+static RE_DID: Lazy<Regex> = Lazy::new(|| Regex::new(r"^did:[a-z0-9]+:\S+$").unwrap());
+
+/// Regular expression that defines the structure of an absolute URI: a scheme followed by ':'.
+static RE_URI: Lazy<Regex> = Lazy::new(|| Regex::new(r"^[A-Za-z][A-Za-z0-9+.-]*:\S+$").unwrap());
+
+/// [ValidationPolicy] holds the application-specific rules applied on top of the rules of the
+/// Identifier Formats by [`SubjectId::validate_with`].
+#[derive(Clone, Debug, Default)]
+pub struct ValidationPolicy {
+    /// Rules for "opaque" identifiers.
+    pub opaque: OpaquePolicy,
+    /// Rules for "phone_number" identifiers.
+    pub phone: PhonePolicy,
+}
+
+/// [OpaquePolicy] restricts the "id" member of "opaque" identifiers. Lengths count characters.
+/// The default policy accepts any non-empty string. The policy is only enforced by
+/// [`SubjectId::validate_with`] and [`Validated::with_policy`]; [`SubjectId::validate`] always
+/// uses the default policy.
+///
+/// ```
+/// use subject_id::{Atomic, OpaquePolicy, SubjectId, ValidationPolicy};
+/// let policy = ValidationPolicy { opaque: OpaquePolicy::uuid(), ..Default::default() };
+/// let sub_id = SubjectId::Atomic(Atomic::Opaque { id: "1i3j4l".to_owned() });
+/// assert!(sub_id.validate().is_ok());
+/// assert!(sub_id.validate_with(&policy).is_err());
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct OpaquePolicy {
+    /// Minimum length, if any.
+    pub min_len: Option<usize>,
+    /// Maximum length, if any.
+    pub max_len: Option<usize>,
+    /// Allowed characters, if restricted. The prefix is exempt.
+    pub alphabet: Option<String>,
+    /// Required prefix, if any. The prefix counts towards the length.
+    pub prefix: Option<String>,
+    /// Required structure of the identifier after the prefix, if any.
+    pub shape: Option<OpaqueShape>,
+}
+
+/// [OpaqueShape] is a structure of "opaque" identifiers that an alphabet and lengths cannot
+/// express, see [OpaquePolicy].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum OpaqueShape {
+    /// Hyphens at positions 8, 13, 18 and 23, as in the 8-4-4-4-12 layout of UUIDs.
+    Uuid,
+    /// A first character of at most `7`, so that the ULID fits in 128 bits.
+    Ulid,
+}
+
+impl OpaqueShape {
+    fn check(&self, id: &str) -> bool {
+        match self {
+            Self::Uuid => {
+                id.len() == 36
+                    && id.bytes().enumerate().all(|(i, b)| match i {
+                        8 | 13 | 18 | 23 => b == b'-',
+                        _ => b.is_ascii_hexdigit(),
+                    })
+            }
+            Self::Ulid => id
+                .bytes()
+                .next()
+                .is_some_and(|b| (b'0'..=b'7').contains(&b)),
+        }
+    }
+}
+
+impl OpaquePolicy {
+    /// Policy for hyphenated UUIDs in lowercase, e.g. `4e851e98-83c4-4743-a5da-150ecb53042f`.
+    pub fn uuid() -> Self {
+        Self {
+            min_len: Some(36),
+            max_len: Some(36),
+            alphabet: Some("0123456789abcdef-".to_owned()),
+            prefix: None,
+            shape: Some(OpaqueShape::Uuid),
+        }
+    }
+
+    /// Policy for ULIDs, in Crockford's base32 uppercase alphabet.
+    pub fn ulid() -> Self {
+        Self {
+            min_len: Some(26),
+            max_len: Some(26),
+            alphabet: Some("0123456789ABCDEFGHJKMNPQRSTVWXYZ".to_owned()),
+            prefix: None,
+            shape: Some(OpaqueShape::Ulid),
+        }
+    }
+
+    /// Check the "id" member of an "opaque" identifier against the policy.
+    pub fn check(&self, id: &str) -> Result<(), Error> {
+        let format = SubjectId::FORMAT_OPAQUE;
+        let rest = match &self.prefix {
+            Some(prefix) => id
+                .strip_prefix(prefix.as_str())
+                .ok_or_else(|| invalid(format, format!("\"id\" does not start with {prefix:?}")))?,
+            None => id,
+        };
+        let len = id.chars().count();
+        if self.min_len.is_some_and(|min| len < min) || self.max_len.is_some_and(|max| len > max) {
+            return Err(invalid(format, format!("\"id\" has invalid length {len}")));
+        }
+        if let Some(alphabet) = &self.alphabet {
+            if let Some(c) = rest.chars().find(|&c| !alphabet.contains(c)) {
+                return Err(invalid(format, format!("\"id\" contains {c:?}")));
+            }
+        }
+        if let Some(shape) = self.shape.filter(|shape| !shape.check(rest)) {
+            return Err(invalid(format, format!("\"id\" is not a {shape:?}")));
+        }
+        Ok(())
+    }
+}
+
+impl SubjectId {
+    /// Validate the [SubjectId] against the rules of its Identifier Format, with the default
+    /// [ValidationPolicy]. Use [`SubjectId::validate_with`] to enforce an application policy.
+    ///
+    /// ```
+    /// use subject_id::{Atomic, SubjectId};
+    /// let sub_id = SubjectId::Atomic(Atomic::Opaque { id: "".to_owned() });
+    /// assert!(sub_id.validate().is_err());
+    /// ```
+    pub fn validate(&self) -> Result<(), Error> {
+        self.validate_with(&ValidationPolicy::default())
+    }
+
+    /// Validate the [SubjectId] against the rules of its Identifier Format and the policy.
+    pub fn validate_with(&self, policy: &ValidationPolicy) -> Result<(), Error> {
+        match self {
+            Self::Atomic(id) => id.validate_with(policy),
+            Self::Aliases(aliases) => aliases.validate_with(policy),
+        }
+    }
+}
+
+impl Aliases {
+    /// Validate the [Aliases]: the "identifiers" member MUST NOT be empty, and every identifier
+    /// MUST be valid.
+    pub fn validate(&self) -> Result<(), Error> {
+        self.validate_with(&ValidationPolicy::default())
+    }
+
+    /// Validate the [Aliases] and every identifier against the policy.
+    pub fn validate_with(&self, policy: &ValidationPolicy) -> Result<(), Error> {
+        if self.identifiers.is_empty() {
+            return Err(invalid(
+                SubjectId::FORMAT_ALIASES,
+                "\"identifiers\" is empty",
+            ));
+        }
+        self.identifiers
+            .iter()
+            .try_for_each(|id| id.validate_with(policy))
+    }
+}
+
+impl Atomic {
+    /// Validate the atomic identifier against the rules of its Identifier Format. Every member is
+    /// REQUIRED and MUST NOT be empty; in addition:
+    ///
+    /// - "account" URIs MUST use the "acct" scheme and have the form `acct:userpart@host`,
+    /// - "email" addresses MUST be formatted as an "addr-spec", see [EmailAddress],
+    /// - "did" URLs MUST have a method name and a method-specific identifier,
+    /// - "uri" URIs MUST be absolute, i.e. start with a scheme.
+    pub fn validate(&self) -> Result<(), Error> {
+        self.validate_with(&ValidationPolicy::default())
+    }
+
+    /// Validate the atomic identifier against the rules of its Identifier Format, see
+    /// [`Atomic::validate`], and against the policy.
+    pub fn validate_with(&self, policy: &ValidationPolicy) -> Result<(), Error> {
+        let format = self.format();
+        if let Some((name, _)) = self.members().iter().find(|(_, v)| v.is_empty()) {
+            return Err(invalid(format, format!("{name:?} is empty")));
+        }
+        match self {
+            Atomic::Account { uri } if !is_acct_uri(uri) => {
+                Err(invalid(format, "\"uri\" is not an acct URI"))
+            }
+            Atomic::Email { email } if email.parse::<EmailAddress>().is_err() => {
+                Err(invalid(format, "\"email\" is not an addr-spec"))
+            }
+            Atomic::Did { url } if !RE_DID.is_match(url) => {
+                Err(invalid(format, "\"url\" is not a DID URL"))
+            }
+            Atomic::Uri { uri } if !RE_URI.is_match(uri) => {
+                Err(invalid(format, "\"uri\" is not an absolute URI"))
+            }
+            Atomic::Opaque { id } => policy.opaque.check(id),
+            Atomic::PhoneNumber { phone_number } => policy.phone.check(phone_number),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Report whether the URI has the form `acct:userpart@host`.
+fn is_acct_uri(uri: &str) -> bool {
+    match uri.split_once(':') {
+        Some((scheme, rest)) if scheme.eq_ignore_ascii_case("acct") => {
+            matches!(rest.rsplit_once('@'), Some((user, host)) if !user.is_empty() && !host.is_empty())
+        }
+        _ => false,
+    }
+}
+
+fn invalid(format: &'static str, reason: impl Into<String>) -> Error {
+    Error::InvalidSubject {
+        format,
+        reason: reason.into(),
+    }
+}
+
+/// [Validated] wraps a [SubjectId] (owned or borrowed) so that serializing it first validates it,
+/// failing with a descriptive error if the subject identifier is not conformant. A transmitter
+/// serializing its events through [Validated] can never put a non-conformant subject identifier
+/// on the wire.
+///
+/// ```
+/// use subject_id::{Atomic, SubjectId, Validated};
+/// let sub_id = SubjectId::Atomic(Atomic::Uri { uri: "not a uri".to_owned() });
+/// let err = serde_json::to_string(&Validated(&sub_id)).unwrap_err();
+/// assert_eq!(err.to_string(), "invalid uri subject identifier: \"uri\" is not an absolute URI");
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Validated<T>(pub T);
+
+impl<T> Validated<T> {
+    /// Validate against the policy rather than the default one when serializing.
+    ///
+    /// ```
+    /// use subject_id::{Atomic, OpaquePolicy, SubjectId, Validated, ValidationPolicy};
+    /// let policy = ValidationPolicy { opaque: OpaquePolicy::uuid(), ..Default::default() };
+    /// let sub_id = SubjectId::Atomic(Atomic::Opaque { id: "1i3j4l".to_owned() });
+    /// assert!(serde_json::to_string(&Validated(&sub_id)).is_ok());
+    /// assert!(serde_json::to_string(&Validated(&sub_id).with_policy(&policy)).is_err());
+    /// ```
+    pub fn with_policy(self, policy: &ValidationPolicy) -> ValidatedWith<'_, T> {
+        ValidatedWith {
+            sub_id: self.0,
+            policy,
+        }
+    }
+}
+
+impl<T: Borrow<SubjectId>> Serialize for Validated<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_validated(self.0.borrow(), serializer)
+    }
+}
+
+/// [ValidatedWith] wraps a [SubjectId] (owned or borrowed) so that serializing it first validates
+/// it against a [ValidationPolicy], see [`Validated::with_policy`].
+#[derive(Clone, Copy, Debug)]
+pub struct ValidatedWith<'p, T> {
+    sub_id: T,
+    policy: &'p ValidationPolicy,
+}
+
+impl<T: Borrow<SubjectId>> Serialize for ValidatedWith<'_, T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_validated_with(self.sub_id.borrow(), self.policy, serializer)
+    }
+}
+
+/// Validate the [SubjectId] and serialize it, for use with `#[serde(serialize_with = "...")]` on
+/// fields holding subject identifiers.
+///
+/// ```
+/// use serde::Serialize;
+/// use subject_id::SubjectId;
+///
+/// #[derive(Serialize)]
+/// struct Event {
+///     #[serde(serialize_with = "subject_id::serialize_validated")]
+///     sub_id: SubjectId,
+/// }
+/// ```
+pub fn serialize_validated<S: Serializer>(
+    sub_id: &SubjectId,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serialize_validated_with(sub_id, &ValidationPolicy::default(), serializer)
+}
+
+/// Validate the [SubjectId] against the policy and serialize it. Wrap it in a function of the
+/// application to use it with `#[serde(serialize_with = "...")]`.
+///
+/// ```
+/// use serde::{Serialize, Serializer};
+/// use subject_id::{OpaquePolicy, SubjectId, ValidationPolicy};
+///
+/// fn serialize_sub_id<S: Serializer>(sub_id: &SubjectId, serializer: S) -> Result<S::Ok, S::Error> {
+///     let policy = ValidationPolicy { opaque: OpaquePolicy::uuid(), ..Default::default() };
+///     subject_id::serialize_validated_with(sub_id, &policy, serializer)
+/// }
+///
+/// #[derive(Serialize)]
+/// struct Event {
+///     #[serde(serialize_with = "serialize_sub_id")]
+///     sub_id: SubjectId,
+/// }
+/// ```
+pub fn serialize_validated_with<S: Serializer>(
+    sub_id: &SubjectId,
+    policy: &ValidationPolicy,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    sub_id.validate_with(policy).map_err(ser::Error::custom)?;
+    sub_id.serialize(serializer)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_validate() {
+        let valid = [
+            Atomic::Account {
+                uri: "acct:example.user@service.example.com".to_owned(),
+            },
+            Atomic::Email {
+                email: "\"john doe\"@example.com".to_owned(),
+            },
+            Atomic::Did {
+                url: "did:example:123456/path?query#fragment".to_owned(),
+            },
+            Atomic::Uri {
+                uri: "urn:uuid:4e851e98-83c4-4743-a5da-150ecb53042f".to_owned(),
+            },
+        ];
+        for id in valid {
+            assert!(id.validate().is_ok(), "{id:?} is valid");
+        }
+        let invalid = [
+            Atomic::Account {
+                uri: "mailto:user@example.com".to_owned(),
+            },
+            Atomic::Email {
+                email: "user".to_owned(),
+            },
+            Atomic::IssuerSubject {
+                issuer: "https://issuer.example.com/".to_owned(),
+                subject: "".to_owned(),
+            },
+            Atomic::Did {
+                url: "did:example".to_owned(),
+            },
+            Atomic::Uri {
+                uri: "/relative/path".to_owned(),
+            },
+        ];
+        for id in invalid {
+            assert!(id.validate().is_err(), "{id:?} is invalid");
+        }
+        assert!(Aliases::from(vec![]).validate().is_err());
+    }
+
+    #[test]
+    fn test_opaque_policy() {
+        let policy = OpaquePolicy {
+            min_len: Some(6),
+            max_len: Some(8),
+            alphabet: Some("0123456789".to_owned()),
+            prefix: Some("u-".to_owned()),
+            ..Default::default()
+        };
+        assert!(policy.check("u-1234").is_ok());
+        for id in ["1234", "x-1234", "u-123", "u-1234567", "u-12a4"] {
+            assert!(policy.check(id).is_err(), "{id} is invalid");
+        }
+        let policy = ValidationPolicy {
+            opaque: OpaquePolicy::ulid(),
+            ..Default::default()
+        };
+        let sub_id = SubjectId::Aliases(Aliases::from(vec![Atomic::Opaque {
+            id: "01ARZ3NDEKTSV4RRFFQ69G5FAV".to_owned(),
+        }]));
+        assert!(sub_id.validate_with(&policy).is_ok());
+
+        let ulid = OpaquePolicy::ulid();
+        assert!(ulid.check("7ZZZZZZZZZZZZZZZZZZZZZZZZZ").is_ok());
+        assert!(ulid.check("8ZZZZZZZZZZZZZZZZZZZZZZZZZ").is_err());
+        let uuid = OpaquePolicy::uuid();
+        assert!(uuid.check("4e851e98-83c4-4743-a5da-150ecb53042f").is_ok());
+        for id in [
+            "------------------------------------",
+            "4e851e9-883c4-4743-a5da-150ecb53042f",
+            "4e851e9883c4-4743-a5da-150ecb53042f-",
+            "4E851E98-83C4-4743-A5DA-150ECB53042F",
+        ] {
+            assert!(uuid.check(id).is_err(), "{id} is invalid");
+        }
+    }
+
+    #[test]
+    fn test_validated_serialize() {
+        let sub_id = SubjectId::Atomic(Atomic::Opaque {
+            id: "1i3j4l".to_owned(),
+        });
+        let got = serde_json::to_string(&Validated(sub_id)).unwrap();
+        assert_eq!(got, r#"{"format":"opaque","id":"1i3j4l"}"#);
+        let sub_id = SubjectId::Aliases(Aliases::from(vec![]));
+        assert!(serde_json::to_string(&Validated(&sub_id)).is_err());
+    }
+
+    #[test]
+    fn test_validated_serialize_with_policy() {
+        let policy = ValidationPolicy {
+            opaque: OpaquePolicy::uuid(),
+            ..Default::default()
+        };
+        let sub_id = SubjectId::Atomic(Atomic::Opaque {
+            id: "1i3j4l".to_owned(),
+        });
+        let err = serde_json::to_string(&Validated(&sub_id).with_policy(&policy)).unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("invalid opaque subject identifier"));
+        let mut out = Vec::new();
+        let mut serializer = serde_json::Serializer::new(&mut out);
+        assert!(serialize_validated_with(&sub_id, &policy, &mut serializer).is_err());
+
+        let sub_id = SubjectId::Atomic(Atomic::Opaque {
+            id: "4e851e98-83c4-4743-a5da-150ecb53042f".to_owned(),
+        });
+        let got = serde_json::to_string(&Validated(&sub_id).with_policy(&policy)).unwrap();
+        assert_eq!(got, serde_json::to_string(&sub_id).unwrap());
+    }
+}
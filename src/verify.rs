@@ -0,0 +1,155 @@
+//! `verify` module checks that the email addresses of subject identifiers are deliverable, for
+//! transmitters that must not emit events about undeliverable addresses. Verification is
+//! pluggable through the [EmailVerifier] trait: [SyntacticVerifier] only requires a valid
+//! "addr-spec", and with the `dns` feature, [MxVerifier] also requires the domain to accept mail.
+
+use crate::{Atomic, EmailAddress, Error, SubjectId};
+use std::future::Future;
+
+/// [EmailVerifier] decides whether an email address is deliverable.
+pub trait EmailVerifier {
+    /// Verify the email address, failing with [`Error::UndeliverableEmail`] if it is not
+    /// deliverable.
+    fn verify(&self, email: &EmailAddress) -> impl Future<Output = Result<(), Error>> + Send;
+}
+
+/// [SyntacticVerifier] accepts every email address that parses as an [EmailAddress].
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SyntacticVerifier;
+
+impl EmailVerifier for SyntacticVerifier {
+    async fn verify(&self, _: &EmailAddress) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl SubjectId {
+    /// Verify every "email" identifier of the [SubjectId] with the verifier. Identifiers of other
+    /// formats are not verified.
+    ///
+    /// ```
+    /// use subject_id::{Atomic, SubjectId, SyntacticVerifier};
+    /// # tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+    /// let sub_id = SubjectId::Atomic(Atomic::Email { email: "user@example.com".to_owned() });
+    /// assert!(sub_id.verify(&SyntacticVerifier).await.is_ok());
+    /// # });
+    /// ```
+    pub async fn verify(&self, verifier: &impl EmailVerifier) -> Result<(), Error> {
+        let identifiers = match self {
+            SubjectId::Atomic(id) => std::slice::from_ref(id),
+            SubjectId::Aliases(aliases) => &aliases.identifiers[..],
+        };
+        for id in identifiers {
+            if let Atomic::Email { email } = id {
+                verifier.verify(&email.parse()?).await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// [MxVerifier] accepts email addresses whose domain accepts mail according to [`RFC5321`]:
+/// the domain has an MX record other than a "null MX" ([`RFC7505`]), or, without MX records, an
+/// address record. Domain literals are accepted as is.
+///
+/// [`RFC5321`]: https://www.rfc-editor.org/rfc/rfc5321#section-5.1
+/// [`RFC7505`]: https://www.rfc-editor.org/rfc/rfc7505
+#[cfg(feature = "dns")]
+#[derive(Clone)]
+pub struct MxVerifier {
+    resolver: hickory_resolver::TokioResolver,
+}
+
+#[cfg(feature = "dns")]
+impl MxVerifier {
+    /// Verifier using the system DNS configuration.
+    pub fn new() -> Result<Self, Error> {
+        let resolver = hickory_resolver::TokioResolver::builder_tokio()?.build()?;
+        Ok(Self { resolver })
+    }
+
+    /// Verifier using the given resolver.
+    pub fn with_resolver(resolver: hickory_resolver::TokioResolver) -> Self {
+        Self { resolver }
+    }
+}
+
+#[cfg(feature = "dns")]
+impl std::fmt::Debug for MxVerifier {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MxVerifier").finish_non_exhaustive()
+    }
+}
+
+#[cfg(feature = "dns")]
+impl EmailVerifier for MxVerifier {
+    async fn verify(&self, email: &EmailAddress) -> Result<(), Error> {
+        use hickory_resolver::proto::rr::RData;
+        let domain = email.domain();
+        if domain.starts_with('[') {
+            return Ok(());
+        }
+        let undeliverable = |reason: &str| Error::UndeliverableEmail(format!("{domain}: {reason}"));
+        let fqdn = domain.trim_end_matches('.').to_owned() + ".";
+        match self.resolver.mx_lookup(fqdn.as_str()).await {
+            Ok(lookup) => {
+                let mut exchanges = lookup.answers().iter().filter_map(|r| match &r.data {
+                    RData::MX(mx) => Some(&mx.exchange),
+                    _ => None,
+                });
+                match exchanges.any(|exchange| !exchange.is_root()) {
+                    true => Ok(()),
+                    false => Err(undeliverable("null MX")),
+                }
+            }
+            Err(err) if err.is_nx_domain() => Err(undeliverable("domain does not exist")),
+            Err(err) if err.is_no_records_found() => {
+                match self.resolver.lookup_ip(fqdn.as_str()).await {
+                    Ok(lookup) if lookup.iter().next().is_some() => Ok(()),
+                    Ok(_) => Err(undeliverable("no MX or address records")),
+                    Err(err) if err.is_no_records_found() => {
+                        Err(undeliverable("no MX or address records"))
+                    }
+                    Err(err) => Err(err.into()),
+                }
+            }
+            Err(err) => Err(err.into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Aliases;
+
+    struct DenyDomain(&'static str);
+
+    impl EmailVerifier for DenyDomain {
+        async fn verify(&self, email: &EmailAddress) -> Result<(), Error> {
+            match email.domain() == self.0 {
+                true => Err(Error::UndeliverableEmail(self.0.to_owned())),
+                false => Ok(()),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_verify() {
+        let email = |email: &str| Atomic::Email {
+            email: email.to_owned(),
+        };
+        let sub_id = SubjectId::Aliases(Aliases::from(vec![
+            Atomic::Opaque {
+                id: "1i3j4l".to_owned(),
+            },
+            email("user@example.com"),
+            email("user@invalid.example"),
+        ]));
+        assert!(sub_id.verify(&SyntacticVerifier).await.is_ok());
+        let got = sub_id.verify(&DenyDomain("invalid.example")).await;
+        assert!(matches!(got, Err(Error::UndeliverableEmail(..))));
+        let sub_id = SubjectId::Atomic(email("not an address"));
+        assert!(sub_id.verify(&SyntacticVerifier).await.is_err());
+    }
+}
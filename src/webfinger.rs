@@ -0,0 +1,238 @@
+//! `webfinger` module expands "account" and "email" identifiers into "aliases" subject
+//! identifiers with a [`WebFinger`] lookup, for receivers that correlate subjects across
+//! federated services. The expansion holds the identifier looked up, the "acct" URI of the
+//! subject, and the URIs the service lists as its aliases and profile pages.
+//!
+//! [`WebFinger`]: https://www.rfc-editor.org/rfc/rfc7033
+
+use crate::http::{is_dns_name, read_body};
+use crate::{Aliases, Atomic, EmailAddress, Error};
+use serde_json::Value;
+
+/// Link relation type of profile pages.
+pub const REL_PROFILE_PAGE: &str = "http://webfinger.net/rel/profile-page";
+
+/// Size limit of the JSON Resource Descriptor read by [expand], in bytes.
+pub const MAX_JRD_LEN: usize = 64 * 1024;
+
+/// Look up the "account" or "email" identifier with WebFinger and expand it into [Aliases], see
+/// [from_jrd]. Email addresses are looked up as the "acct" URI of the same address.
+///
+/// Only hosts that are DNS names with at least two labels are queried: IP literals, ports,
+/// "localhost" and hosts containing URL delimiters are rejected, so that identifiers cannot point
+/// the request at arbitrary endpoints. Responses larger than [MAX_JRD_LEN] are rejected. The
+/// client should not follow redirects to other hosts.
+pub async fn expand(client: &reqwest::Client, id: &Atomic) -> Result<Aliases, Error> {
+    let (resource, host) = resource(id)?;
+    let url = reqwest::Url::parse_with_params(
+        &format!("https://{host}/.well-known/webfinger"),
+        [("resource", &resource)],
+    )
+    .map_err(|err| invalid(id, err.to_string()))?;
+    let response = client
+        .get(url)
+        .header(reqwest::header::ACCEPT, "application/jrd+json")
+        .send()
+        .await?
+        .error_for_status()?;
+    let body = read_body(response, MAX_JRD_LEN).await?;
+    let jrd: Value = serde_json::from_slice(&body)?;
+    Ok(from_jrd(id, &jrd))
+}
+
+/// Expand the identifier with the JSON Resource Descriptor returned for it: the identifier
+/// itself, the "subject" of the descriptor as an "account" identifier if it is an "acct" URI,
+/// and every "aliases" URI and profile page link as "uri" identifiers, without duplicates.
+///
+/// Only the subject, aliases and links on the host of the identifier are kept, since a host is
+/// only authoritative for its own accounts and pages; the descriptor of a hostile host cannot
+/// claim accounts or URIs elsewhere.
+///
+/// ```
+/// use serde_json::json;
+/// use subject_id::{webfinger, Atomic};
+/// let id = Atomic::Email { email: "alice@example.com".to_owned() };
+/// let jrd = json!({
+///     "subject": "acct:alice@example.com",
+///     "aliases": ["https://example.com/@alice"],
+/// });
+/// let aliases = webfinger::from_jrd(&id, &jrd);
+/// assert_eq!(aliases.identifiers.len(), 3);
+/// ```
+pub fn from_jrd(id: &Atomic, jrd: &Value) -> Aliases {
+    let mut identifiers = vec![id.clone()];
+    let Ok((_, host)) = resource(id) else {
+        return Aliases { identifiers };
+    };
+    let on_host = |uri: &str| {
+        reqwest::Url::parse(uri).is_ok_and(|url| {
+            matches!(url.scheme(), "http" | "https")
+                && url
+                    .host_str()
+                    .is_some_and(|h| h.eq_ignore_ascii_case(&host))
+        })
+    };
+    let mut push = |id: Atomic| {
+        if !identifiers.contains(&id) {
+            identifiers.push(id);
+        }
+    };
+    if let Some(subject) = jrd.get("subject").and_then(Value::as_str) {
+        let subject_host = subject
+            .strip_prefix("acct:")
+            .and_then(|address| address.rsplit_once('@'))
+            .map(|(_, h)| h);
+        if subject_host.is_some_and(|h| h.eq_ignore_ascii_case(&host)) {
+            push(Atomic::Account {
+                uri: subject.to_owned(),
+            });
+        }
+    }
+    let aliases = jrd.get("aliases").and_then(Value::as_array);
+    for alias in aliases.into_iter().flatten().filter_map(Value::as_str) {
+        if !on_host(alias) {
+            continue;
+        }
+        push(Atomic::Uri {
+            uri: alias.to_owned(),
+        });
+    }
+    let links = jrd.get("links").and_then(Value::as_array);
+    for link in links.into_iter().flatten() {
+        let rel = link.get("rel").and_then(Value::as_str);
+        if let (Some(REL_PROFILE_PAGE), Some(href)) =
+            (rel, link.get("href").and_then(Value::as_str))
+        {
+            if !on_host(href) {
+                continue;
+            }
+            push(Atomic::Uri {
+                uri: href.to_owned(),
+            });
+        }
+    }
+    Aliases { identifiers }
+}
+
+/// The "acct" URI to look up and the host to query. Quoted local parts cannot be written in an
+/// "acct" URI without percent-encoding and are rejected.
+fn resource(id: &Atomic) -> Result<(String, String), Error> {
+    let address = match id {
+        Atomic::Account { uri } => uri.strip_prefix("acct:").unwrap_or(uri),
+        Atomic::Email { email } => email,
+        _ => return Err(invalid(id, "only account and email identifiers")),
+    };
+    let address: EmailAddress = address
+        .parse()
+        .map_err(|_| invalid(id, "not an addr-spec"))?;
+    if !is_dns_name(address.domain()) {
+        return Err(invalid(id, "host is not a public DNS name"));
+    }
+    let host = address.domain().to_owned();
+    let address = address.to_string();
+    if address.starts_with('"') {
+        return Err(invalid(id, "quoted local part"));
+    }
+    Ok((format!("acct:{address}"), host))
+}
+
+fn invalid(id: &Atomic, reason: impl Into<String>) -> Error {
+    Error::InvalidSubject {
+        format: id.format(),
+        reason: reason.into(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_from_jrd() {
+        let id = Atomic::Account {
+            uri: "acct:alice@example.com".to_owned(),
+        };
+        let jrd = json!({
+            "subject": "acct:alice@example.com",
+            "aliases": ["https://example.com/@alice", "https://example.com/users/alice"],
+            "links": [
+                {"rel": "self", "href": "https://example.com/users/alice"},
+                {"rel": REL_PROFILE_PAGE, "href": "https://example.com/@alice"},
+                {"rel": REL_PROFILE_PAGE, "href": "https://Example.com/alice"},
+                {"rel": REL_PROFILE_PAGE, "href": "https://blog.example.com/alice"},
+            ],
+        });
+        let want = vec![
+            id.clone(),
+            Atomic::Uri {
+                uri: "https://example.com/@alice".to_owned(),
+            },
+            Atomic::Uri {
+                uri: "https://example.com/users/alice".to_owned(),
+            },
+            Atomic::Uri {
+                uri: "https://Example.com/alice".to_owned(),
+            },
+        ];
+        assert_eq!(from_jrd(&id, &jrd).identifiers, want);
+    }
+
+    #[test]
+    fn test_from_jrd_cross_domain() {
+        let id = Atomic::Email {
+            email: "alice@example.com".to_owned(),
+        };
+        let jrd = json!({
+            "subject": "acct:ceo@other.example",
+            "aliases": ["https://other.example/@ceo", "mailto:ceo@example.com"],
+            "links": [
+                {"rel": REL_PROFILE_PAGE, "href": "https://example.com.evil.example/alice"},
+            ],
+        });
+        assert_eq!(from_jrd(&id, &jrd).identifiers, vec![id]);
+    }
+
+    #[test]
+    fn test_resource() {
+        let email = Atomic::Email {
+            email: "bob@example.org".to_owned(),
+        };
+        let want = ("acct:bob@example.org".to_owned(), "example.org".to_owned());
+        assert_eq!(resource(&email).unwrap(), want);
+        let opaque = Atomic::Opaque {
+            id: "bob@example.org".to_owned(),
+        };
+        assert!(resource(&opaque).is_err());
+    }
+
+    #[test]
+    fn test_resource_hostile_host() {
+        let hostile = [
+            "bob@localhost",
+            "bob@internal.localhost",
+            "bob@127.0.0.1",
+            "bob@[127.0.0.1]",
+            "bob@[IPv6:::1]",
+            "bob@intranet",
+            "bob@example.org:8080",
+            "bob@example.org/admin",
+            "bob@example.org?x=1",
+            "bob@example.org#x",
+            "\"bob smith\"@example.org",
+            "bob@-example.org",
+            "bob@example..org",
+            "bob",
+        ];
+        for email in hostile {
+            let id = Atomic::Email {
+                email: email.to_owned(),
+            };
+            assert!(resource(&id).is_err(), "{email}");
+            let id = Atomic::Account {
+                uri: format!("acct:{email}"),
+            };
+            assert!(resource(&id).is_err(), "acct:{email}");
+        }
+    }
+}